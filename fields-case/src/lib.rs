@@ -0,0 +1,190 @@
+//! Case conversion of field identifiers
+//!
+//! `fields_case` holds the casing conventions shared by `fields` and
+//! `fields_derive`, so that `rename_all` at compile time and
+//! `Fields::fields_with_case` at runtime convert names the same way. It's a
+//! transient dependency and should not be used directly.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Casing convention applied to field identifiers, by the `rename_all`
+/// attribute at compile time and by `Fields::fields_with_case` at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// `lowercase`
+    Lower,
+    /// `UPPERCASE`
+    Upper,
+    /// `PascalCase`
+    Pascal,
+    /// `camelCase`
+    Camel,
+    /// `snake_case`
+    Snake,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+    /// `kebab-case`
+    Kebab,
+    /// `SCREAMING-KEBAB-CASE`
+    ScreamingKebab,
+    /// `Train-Case`
+    Train,
+    /// `dot.case`
+    Dot,
+    /// `Title Case`
+    Title,
+}
+
+impl Case {
+    /// All conventions, in the order of their discriminants. Path-safe
+    /// conventions come first, so that their discriminants index tables of
+    /// names converted to the path-safe conventions only.
+    pub const ALL: [Case; 11] = [
+        Case::Lower,
+        Case::Upper,
+        Case::Pascal,
+        Case::Camel,
+        Case::Snake,
+        Case::ScreamingSnake,
+        Case::Kebab,
+        Case::ScreamingKebab,
+        Case::Train,
+        Case::Dot,
+        Case::Title,
+    ];
+
+    /// Convention named `name`, as written in `rename_all`, e.g. `camelCase`.
+    pub fn from_name(name: &str) -> Option<Case> {
+        Case::ALL.into_iter().find(|case| case.name() == name)
+    }
+
+    /// Name of the convention as written in `rename_all`, e.g. `camelCase`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Case::Lower => "lowercase",
+            Case::Upper => "UPPERCASE",
+            Case::Pascal => "PascalCase",
+            Case::Camel => "camelCase",
+            Case::Snake => "snake_case",
+            Case::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+            Case::Kebab => "kebab-case",
+            Case::ScreamingKebab => "SCREAMING-KEBAB-CASE",
+            Case::Train => "Train-Case",
+            Case::Dot => "dot.case",
+            Case::Title => "Title Case",
+        }
+    }
+
+    /// Whether names in the convention can be segments of dotted field
+    /// paths, i.e. contain neither `.` nor spaces. Only such conventions are
+    /// accepted by `rename_all`.
+    pub const fn is_path_safe(&self) -> bool {
+        !matches!(self, Case::Dot | Case::Title)
+    }
+
+    /// Convert `ident` to the convention. Words are split at `_`, `-`, `.`
+    /// and spaces, at lowercase to uppercase transitions and at the end of
+    /// acronyms, so `parseHTTPResponse` in snake case is
+    /// `parse_http_response`. Digits stay attached to the word they follow.
+    /// `lowercase` and `UPPERCASE` drop `_` and `-` instead.
+    pub fn apply(&self, ident: &str) -> String {
+        match self {
+            Case::Lower => ident.to_lowercase().replace(['_', '-'], ""),
+            Case::Upper => ident.to_uppercase().replace(['_', '-'], ""),
+            Case::Pascal => join_words(ident, "", |_, word| capitalize(word)),
+            Case::Camel => join_words(ident, "", |i, word| match i {
+                0 => word.to_lowercase(),
+                _ => capitalize(word),
+            }),
+            Case::Snake => join_words(ident, "_", |_, word| word.to_lowercase()),
+            Case::ScreamingSnake => join_words(ident, "_", |_, word| word.to_uppercase()),
+            Case::Kebab => join_words(ident, "-", |_, word| word.to_lowercase()),
+            Case::ScreamingKebab => join_words(ident, "-", |_, word| word.to_uppercase()),
+            Case::Train => join_words(ident, "-", |_, word| capitalize(word)),
+            Case::Dot => join_words(ident, ".", |_, word| word.to_lowercase()),
+            Case::Title => join_words(ident, " ", |_, word| capitalize(word)),
+        }
+    }
+}
+
+// Path-safe conventions come first in `Case::ALL`.
+const _: () = {
+    let mut i = 1;
+    while i < Case::ALL.len() {
+        assert!(Case::ALL[i - 1].is_path_safe() || !Case::ALL[i].is_path_safe());
+        i += 1;
+    }
+};
+
+/// Words of an identifier, split at `_`, `-`, `.` and spaces, before an
+/// uppercase letter following a lowercase letter or a digit, and before the
+/// last letter of an acronym followed by a lowercase letter. For example
+/// `parseHTTPResponse2xx_code` is `parse`, `HTTP`, `Response2xx` and `code`.
+fn split_words(ident: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = ident.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, &(index, char)) in chars.iter().enumerate() {
+        if matches!(char, '_' | '-' | '.' | ' ') {
+            if let Some(start) = start.take() {
+                words.push(&ident[start..index]);
+            }
+            continue;
+        }
+        match start {
+            Some(word_start) => {
+                let previous = chars[i - 1].1;
+                let next = chars.get(i + 1).map(|&(_, next)| next);
+                let boundary = char.is_uppercase()
+                    && (previous.is_lowercase()
+                        || previous.is_numeric()
+                        || previous.is_uppercase() && next.is_some_and(char::is_lowercase));
+                if boundary {
+                    words.push(&ident[word_start..index]);
+                    start = Some(index);
+                }
+            }
+            None => start = Some(index),
+        }
+    }
+    if let Some(start) = start {
+        words.push(&ident[start..]);
+    }
+    words
+}
+
+/// Words of `ident` transformed with `word`, which also receives the index of
+/// the word, joined with `separator`. Leading and trailing underscores, as in
+/// `_private` or `type_`, are kept as separators.
+fn join_words<F>(ident: &str, separator: &str, word: F) -> String
+where
+    F: Fn(usize, &str) -> String,
+{
+    let words: Vec<String> = split_words(ident)
+        .into_iter()
+        .enumerate()
+        .map(|(i, w)| word(i, w))
+        .collect();
+    if words.is_empty() {
+        return ident.to_string();
+    }
+    let leading = ident.len() - ident.trim_start_matches('_').len();
+    let trailing = ident.len() - ident.trim_end_matches('_').len();
+    separator.repeat(leading) + &words.join(separator) + &separator.repeat(trailing)
+}
+
+/// `word` with its first letter uppercase and the rest lowercase.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.as_str().to_lowercase().chars())
+            .collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,145 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, GenericParam, Member, Type};
+
+use crate::attributes::field::parse_field_attributes;
+use crate::attributes::structure::StructAttributes;
+use crate::parse::is_skipped;
+
+/// `ApplyMask` implementation moving the fields selected by a mask from the
+/// source into `self`, recursing into nested fields. Enums are replaced as a
+/// whole when anything in them is selected, as the variants of the source and
+/// of `self` may differ.
+pub(crate) fn derive_apply_mask(
+    input: &DeriveInput,
+    struct_attrs: &StructAttributes,
+) -> syn::Result<TokenStream> {
+    let crate_path = &struct_attrs.crate_path;
+    let name = &input.ident;
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        Data::Enum(_) => {
+            let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+            return Ok(quote! {
+                impl #impl_generics #crate_path::ApplyMask for #name #type_generics #where_clause {
+                    fn __apply_mask(
+                        &mut self,
+                        source: Self,
+                        mask: &#crate_path::FieldMask,
+                        _: ::core::option::Option<#crate_path::Case>,
+                    ) {
+                        if !mask.is_empty() {
+                            *self = source;
+                        }
+                    }
+                }
+            });
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Attribute `apply_mask` is only applicable to structs and enums",
+            ))
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    let type_params: Vec<_> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(type_param) => Some(type_param.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for type_param in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#type_param: #crate_path::ApplyMask));
+    }
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let mut members = Vec::new();
+    let mut bindings = Vec::new();
+    let mut statements = Vec::new();
+    let fields = data.fields.iter().enumerate().filter_map(|(i, field)| {
+        let field_attrs = parse_field_attributes(&field.attrs);
+        (!is_skipped(&field_attrs, struct_attrs)).then_some((i, field, field_attrs))
+    });
+    // Index of the field among the non-skipped ones, i.e. in `FIELDS`.
+    for (index, (i, field, field_attrs)) in fields.enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(i.into()),
+        };
+        let binding = format_ident!("__field_{}", index);
+        let recurse = field_attrs.nested.unwrap_or(struct_attrs.nested)
+            && field_attrs.with.is_none()
+            && matches!(field.ty, Type::Path(_));
+
+        let statement = if struct_attrs.transparent {
+            if recurse {
+                quote! {
+                    #crate_path::ApplyMask::__apply_mask(&mut self.#member, #binding, mask, case);
+                }
+            } else {
+                quote! {
+                    if !mask.is_empty() {
+                        self.#member = #binding;
+                    }
+                }
+            }
+        } else {
+            let selection = quote! {
+                #crate_path::__private::select_field(
+                    <Self as #crate_path::Fields>::FIELDS,
+                    #index,
+                    mask,
+                    case,
+                )
+            };
+            match (recurse, &field.ident) {
+                (true, _) => quote! {
+                    match #selection {
+                        #crate_path::__private::MaskSelection::All => self.#member = #binding,
+                        #crate_path::__private::MaskSelection::Nested(mask, case) => {
+                            #crate_path::ApplyMask::__apply_mask(
+                                &mut self.#member,
+                                #binding,
+                                &mask,
+                                case,
+                            )
+                        }
+                        #crate_path::__private::MaskSelection::None => {}
+                    }
+                },
+                (false, Some(_)) => quote! {
+                    if !::core::matches!(#selection, #crate_path::__private::MaskSelection::None) {
+                        self.#member = #binding;
+                    }
+                },
+                // Unnamed leaves have no paths that could select them.
+                (false, None) => continue,
+            }
+        };
+        members.push(member);
+        bindings.push(binding);
+        statements.push(statement);
+    }
+
+    Ok(quote! {
+        impl #impl_generics #crate_path::ApplyMask for #name #type_generics #where_clause {
+            #[allow(unused_variables)]
+            fn __apply_mask(
+                &mut self,
+                source: Self,
+                mask: &#crate_path::FieldMask,
+                case: ::core::option::Option<#crate_path::Case>,
+            ) {
+                let Self { #(#members: #bindings,)* .. } = source;
+                #(#statements)*
+            }
+        }
+    })
+}
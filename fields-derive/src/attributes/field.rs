@@ -1,22 +1,32 @@
-use proc_macro_error::abort;
+use fields_case::Case;
+use proc_macro2::TokenStream;
+use proc_macro_error2::emit_error;
+use quote::quote;
 use syn::punctuated::Punctuated;
-use syn::{Attribute, Expr, Lit, Meta, Token};
+use syn::{Attribute, Expr, ExprLit, ExprUnary, Lit, LitStr, Meta, Path, Token, Type, UnOp};
 
-#[derive(Debug)]
+use super::structure::parse_rename_all;
+use super::{
+    attribute_name, parse_fields_attributes, parse_flag, parse_lit_bool, parse_lit_str,
+    parse_profiles, unrecognized_attribute,
+};
+
+#[derive(Debug, Default)]
 pub(crate) struct FieldAttributes {
     pub skip: bool,
+    pub include: Option<Path>,
     pub rename: Option<String>,
-    pub nested: bool,
-}
-
-impl Default for FieldAttributes {
-    fn default() -> Self {
-        Self {
-            skip: false,
-            rename: None,
-            nested: true,
-        }
-    }
+    pub rename_profiles: Vec<(String, String)>,
+    pub rename_all_nested: Option<Case>,
+    pub nested: Option<bool>,
+    pub with: Option<With>,
+    pub groups: Vec<String>,
+    pub aliases: Vec<LitStr>,
+    pub deprecated: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub sensitive: bool,
+    pub meta: Vec<(String, MetaLit)>,
 }
 
 impl FieldAttributes {
@@ -24,71 +34,322 @@ impl FieldAttributes {
         self.skip = true;
     }
 
+    fn include(&mut self, path: Path) {
+        self.include = Some(path);
+    }
+
     fn rename(&mut self, new_name: String) {
         self.rename = Some(new_name);
     }
 
+    fn rename_profile(&mut self, profile: String, new_name: String) {
+        self.rename_profiles
+            .retain(|(existing, _)| *existing != profile);
+        self.rename_profiles.push((profile, new_name));
+    }
+
+    fn rename_all_nested(&mut self, rename_all: Case) {
+        self.rename_all_nested = Some(rename_all);
+    }
+
     fn nested(&mut self, value: bool) {
-        self.nested = value;
+        self.nested = Some(value);
+    }
+
+    fn with(&mut self, meta: &Meta, with: With) -> syn::Result<()> {
+        if self.with.is_some() {
+            return Err(syn::Error::new_spanned(
+                meta,
+                "Attributes `with` and `with_fn` can't be used together",
+            ));
+        }
+        self.with = Some(with);
+        Ok(())
+    }
+
+    fn group(&mut self, group: String) {
+        if !self.groups.contains(&group) {
+            self.groups.push(group);
+        }
+    }
+
+    fn alias(&mut self, alias: LitStr) {
+        if !self
+            .aliases
+            .iter()
+            .any(|existing| existing.value() == alias.value())
+        {
+            self.aliases.push(alias);
+        }
+    }
+
+    fn deprecated(&mut self, reason: String) {
+        self.deprecated = Some(reason);
+    }
+
+    fn since(&mut self, version: String) {
+        self.since = Some(version);
+    }
+
+    fn until(&mut self, version: String) {
+        self.until = Some(version);
+    }
+
+    fn sensitive(&mut self) {
+        self.sensitive = true;
+    }
+
+    fn meta(&mut self, entries: Vec<(String, MetaLit)>) {
+        insert_meta(&mut self.meta, entries);
     }
 }
 
-pub(crate) fn parse_field_attributes(attributes: &Vec<Attribute>) -> FieldAttributes {
-    let mut attrs = FieldAttributes::default();
+/// Typed literal value of a `meta(key = value)` entry.
+#[derive(Debug, Clone)]
+pub(crate) enum MetaLit {
+    Str(String),
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+}
 
-    for attr in attributes {
-        if attr.path().is_ident("fields") {
-            let nested = attr
-                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
-                .expect("Cannot parse attribute tokens");
-
-            for meta in nested {
-                match meta {
-                    Meta::Path(path) if path.is_ident("skip") => attrs.skip(),
-                    Meta::NameValue(value) if value.path.is_ident("rename") => {
-                        if let Expr::Lit(expr_lit) = value.value {
-                            match expr_lit.lit {
-                                Lit::Str(str_lit) => {
-                                    let new_name = str_lit.value();
-                                    attrs.rename(new_name);
-                                }
-                                _ => abort!(
-                                    expr_lit.lit,
-                                    "Attribute `rename` expects string literal as value"
-                                ),
-                            }
-                        } else {
-                            abort!(value.value, "Attribute `rename` expects literal as value");
-                        }
-                    }
-                    Meta::NameValue(value) if value.path.is_ident("nested") => {
-                        if let Expr::Lit(expr_lit) = value.value {
-                            match expr_lit.lit {
-                                Lit::Bool(bool_lit) => {
-                                    let new_value = bool_lit.value();
-                                    attrs.nested(new_value);
-                                }
-                                _ => abort!(
-                                    expr_lit.lit,
-                                    "Attribute `nested` expects bool literal as value"
-                                ),
-                            }
-                        } else {
-                            abort!(value.value, "Attribute `nested` expects literal as value")
-                        }
-                    }
+impl MetaLit {
+    pub fn to_tokens(&self, crate_path: &Path) -> TokenStream {
+        match self {
+            MetaLit::Str(value) => quote! { #crate_path::MetaValue::Str(#value) },
+            MetaLit::Bool(value) => quote! { #crate_path::MetaValue::Bool(#value) },
+            MetaLit::Int(value) => quote! { #crate_path::MetaValue::Int(#value) },
+            MetaLit::Float(value) => quote! { #crate_path::MetaValue::Float(#value) },
+        }
+    }
+}
+
+/// Source of the nested fields of a field set with the `with` or `with_fn`
+/// attribute.
+#[derive(Debug)]
+pub(crate) enum With {
+    /// Type implementing `Fields` given to `with`, e.g. the mirror of a remote
+    /// type.
+    Type(Type),
+    /// Function given to `with_fn`, returning the nested field paths at
+    /// runtime.
+    Provider(Path),
+}
+
+/// Insert metadata entries, replacing existing entries with the same key.
+pub(crate) fn insert_meta(meta: &mut Vec<(String, MetaLit)>, entries: Vec<(String, MetaLit)>) {
+    for (key, value) in entries {
+        match meta.iter_mut().find(|(existing, _)| *existing == key) {
+            Some(entry) => entry.1 = value,
+            None => meta.push((key, value)),
+        }
+    }
+}
+
+/// Entries of `meta(key = value, flag)`, where values are string, bool, integer
+/// or float literals and a bare key stands for `true`.
+pub(crate) fn parse_meta_entries(meta: &Meta) -> syn::Result<Vec<(String, MetaLit)>> {
+    let list = match meta {
+        Meta::List(list) => list,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                meta,
+                "Attribute `meta` expects a list of entries, e.g. `meta(key = \"value\")`",
+            ))
+        }
+    };
+
+    let mut entries = Vec::new();
+    let nested = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+    for entry in nested {
+        let key = match entry.path().get_ident() {
+            Some(ident) => ident.to_string(),
+            None => {
+                emit_error!(entry.path(), "Metadata keys must be identifiers");
+                continue;
+            }
+        };
+        let value = match &entry {
+            Meta::Path(_) => Ok(MetaLit::Bool(true)),
+            Meta::NameValue(value) => parse_meta_value(&value.value),
+            Meta::List(_) => Err(syn::Error::new_spanned(
+                &entry,
+                "Metadata values must be literals, e.g. `key = \"value\"`",
+            )),
+        };
+        match value {
+            Ok(value) => entries.push((key, value)),
+            Err(e) => emit_error!(e),
+        }
+    }
+
+    Ok(entries)
+}
+
+fn parse_meta_value(expr: &Expr) -> syn::Result<MetaLit> {
+    let (lit, negative) = match expr {
+        Expr::Lit(ExprLit { lit, .. }) => (lit, false),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            Expr::Lit(ExprLit {
+                lit: lit @ (Lit::Int(_) | Lit::Float(_)),
+                ..
+            }) => (lit, true),
+            _ => return Err(unsupported_meta_value(expr)),
+        },
+        _ => return Err(unsupported_meta_value(expr)),
+    };
+
+    match lit {
+        Lit::Str(str_lit) => Ok(MetaLit::Str(str_lit.value())),
+        Lit::Bool(bool_lit) => Ok(MetaLit::Bool(bool_lit.value())),
+        Lit::Int(int_lit) => {
+            let value: i128 = int_lit.base10_parse()?;
+            let value = if negative { -value } else { value };
+            i64::try_from(value).map(MetaLit::Int).map_err(|_| {
+                syn::Error::new_spanned(expr, "Metadata integers must fit in an `i64`")
+            })
+        }
+        Lit::Float(float_lit) => {
+            let value: f64 = float_lit.base10_parse()?;
+            Ok(MetaLit::Float(if negative { -value } else { value }))
+        }
+        _ => Err(unsupported_meta_value(expr)),
+    }
+}
+
+fn unsupported_meta_value(expr: &Expr) -> syn::Error {
+    syn::Error::new_spanned(
+        expr,
+        "Metadata values must be string, bool, integer or float literals",
+    )
+}
+
+/// Attributes valid on a field.
+const FIELD_ATTRIBUTES: &[&str] = &[
+    "skip",
+    "include",
+    "rename",
+    "rename_all_nested",
+    "nested",
+    "with",
+    "with_fn",
+    "group",
+    "alias",
+    "deprecated",
+    "since",
+    "until",
+    "sensitive",
+    "meta",
+];
 
-                    other => {
-                        let ident = other
-                            .path()
-                            .get_ident()
-                            .expect("Cannot get identifier for unrecognized attribute");
-                        abort!(ident, "Unrecognized attribute `{}`", ident)
+/// Attributes of a field. Invalid attributes are reported and ignored.
+pub(crate) fn parse_field_attributes(attributes: &[Attribute]) -> FieldAttributes {
+    let mut attrs = FieldAttributes::default();
+
+    for meta in parse_fields_attributes(attributes) {
+        let result = match attribute_name(&meta).as_deref() {
+            Some(name @ "skip") => parse_flag(&meta, name).map(|()| attrs.skip()),
+            Some(name @ "include") => {
+                parse_flag(&meta, name).map(|()| attrs.include(meta.path().clone()))
+            }
+            Some(name @ "rename") if matches!(meta, Meta::List(_)) => parse_profiles(&meta, name)
+                .map(|profiles| {
+                    for (profile, str_lit) in profiles {
+                        attrs.rename_profile(profile, str_lit.value());
                     }
-                }
+                }),
+            Some(name @ "rename") => {
+                parse_lit_str(&meta, name).map(|str_lit| attrs.rename(str_lit.value()))
+            }
+            Some(name @ "rename_all_nested") => {
+                parse_rename_all(&meta, name).map(|rename_all| attrs.rename_all_nested(rename_all))
+            }
+            Some(name @ "with") => parse_lit_str(&meta, name).and_then(|str_lit| {
+                let with = str_lit.parse::<Type>().map_err(|e| {
+                    syn::Error::new_spanned(&str_lit, format!("Invalid type: {}", e))
+                })?;
+                attrs.with(&meta, With::Type(with))
+            }),
+            Some(name @ "with_fn") => parse_lit_str(&meta, name).and_then(|str_lit| {
+                let with_fn = str_lit.parse::<Path>().map_err(|e| {
+                    syn::Error::new_spanned(&str_lit, format!("Invalid function path: {}", e))
+                })?;
+                attrs.with(&meta, With::Provider(with_fn))
+            }),
+            Some(name @ "nested") => {
+                parse_lit_bool(&meta, name).map(|bool_lit| attrs.nested(bool_lit.value()))
+            }
+            Some(name @ "group") => {
+                parse_lit_str(&meta, name).map(|str_lit| attrs.group(str_lit.value()))
+            }
+            Some(name @ "alias") => parse_lit_str(&meta, name).map(|str_lit| attrs.alias(str_lit)),
+            Some("deprecated") if matches!(meta, Meta::Path(_)) => {
+                attrs.deprecated(String::new());
+                Ok(())
+            }
+            Some(name @ "deprecated") => {
+                parse_lit_str(&meta, name).map(|str_lit| attrs.deprecated(str_lit.value()))
             }
+            Some(name @ "since") => {
+                parse_lit_str(&meta, name).map(|str_lit| attrs.since(str_lit.value()))
+            }
+            Some(name @ "until") => {
+                parse_lit_str(&meta, name).map(|str_lit| attrs.until(str_lit.value()))
+            }
+            Some(name @ "sensitive") => parse_flag(&meta, name).map(|()| attrs.sensitive()),
+            Some("meta") => parse_meta_entries(&meta).map(|entries| attrs.meta(entries)),
+            _ => {
+                unrecognized_attribute(&meta, "field", FIELD_ATTRIBUTES);
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            emit_error!(e);
+        }
+    }
+
+    if let Some(include) = attrs.include.as_ref().filter(|_| attrs.skip) {
+        emit_error!(
+            include,
+            "Attributes `skip` and `include` can't be used together"
+        );
+    }
+
+    if attrs.deprecated.is_none() {
+        if let Some(reason) = parse_rust_deprecated(attributes) {
+            attrs.deprecated(reason);
         }
     }
 
     attrs
 }
+
+/// Reason of Rust's `#[deprecated]` attribute, taken from its note. Malformed
+/// attributes are left for the compiler to report.
+fn parse_rust_deprecated(attributes: &[Attribute]) -> Option<String> {
+    let attr = attributes
+        .iter()
+        .find(|attr| attr.path().is_ident("deprecated"))?;
+    let note = match &attr.meta {
+        Meta::NameValue(value) => match &value.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(str_lit),
+                ..
+            }) => Some(str_lit.value()),
+            _ => None,
+        },
+        Meta::List(_) => attr
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .ok()?
+            .iter()
+            .find(|meta| meta.path().is_ident("note"))
+            .and_then(|meta| parse_lit_str(meta, "note").ok())
+            .map(|str_lit| str_lit.value()),
+        Meta::Path(_) => None,
+    };
+    Some(note.unwrap_or_default())
+}
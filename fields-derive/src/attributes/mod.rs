@@ -1,2 +1,174 @@
 pub mod field;
 pub mod structure;
+
+use proc_macro_error2::emit_error;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, Lit, LitBool, LitStr, Meta, Token};
+
+/// Arguments of all `#[fields(...)]` attributes. Malformed attributes are
+/// reported and skipped.
+pub(crate) fn parse_fields_attributes(attributes: &[Attribute]) -> Vec<Meta> {
+    let mut metas = Vec::new();
+    for attr in attributes {
+        if attr.path().is_ident("fields") {
+            let nested = match &attr.meta {
+                Meta::List(_) => {
+                    attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                }
+                _ => Err(syn::Error::new_spanned(
+                    attr,
+                    "Attribute `fields` expects a list of arguments, e.g. `#[fields(skip)]`",
+                )),
+            };
+            match nested {
+                Ok(nested) => metas.extend(nested),
+                Err(e) => emit_error!(e),
+            }
+        }
+    }
+    metas
+}
+
+/// Name of the attribute if its path is a single identifier.
+pub(crate) fn attribute_name(meta: &Meta) -> Option<String> {
+    meta.path().get_ident().map(|ident| ident.to_string())
+}
+
+pub(crate) fn parse_flag(meta: &Meta, name: &str) -> syn::Result<()> {
+    match meta {
+        Meta::Path(_) => Ok(()),
+        _ => Err(syn::Error::new_spanned(
+            meta,
+            format!("Attribute `{}` does not take a value", name),
+        )),
+    }
+}
+
+pub(crate) fn parse_lit_str(meta: &Meta, name: &str) -> syn::Result<LitStr> {
+    match parse_lit(meta, name)? {
+        Lit::Str(str_lit) => Ok(str_lit),
+        lit => Err(syn::Error::new_spanned(
+            lit,
+            format!("Attribute `{}` expects string literal as value", name),
+        )),
+    }
+}
+
+pub(crate) fn parse_lit_bool(meta: &Meta, name: &str) -> syn::Result<LitBool> {
+    match parse_lit(meta, name)? {
+        Lit::Bool(bool_lit) => Ok(bool_lit),
+        lit => Err(syn::Error::new_spanned(
+            lit,
+            format!("Attribute `{}` expects bool literal as value", name),
+        )),
+    }
+}
+
+/// Entries of a `name(profile = "value", ...)` list of naming profiles.
+/// Malformed entries are reported and skipped.
+pub(crate) fn parse_profiles(meta: &Meta, name: &str) -> syn::Result<Vec<(String, LitStr)>> {
+    let list = match meta {
+        Meta::List(list) => list,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                meta,
+                format!("Attribute `{}` expects a list of profiles", name),
+            ))
+        }
+    };
+
+    let mut profiles = Vec::new();
+    let nested = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+    for entry in nested {
+        let profile = match entry.path().get_ident() {
+            Some(ident) => ident.to_string(),
+            None => {
+                emit_error!(entry.path(), "Profile names must be identifiers");
+                continue;
+            }
+        };
+        match parse_lit_str(&entry, &profile) {
+            Ok(value) => profiles.push((profile, value)),
+            Err(e) => emit_error!(e),
+        }
+    }
+
+    Ok(profiles)
+}
+
+fn parse_lit(meta: &Meta, name: &str) -> syn::Result<Lit> {
+    match meta {
+        Meta::NameValue(value) => match &value.value {
+            Expr::Lit(expr_lit) => Ok(expr_lit.lit.clone()),
+            other => Err(syn::Error::new_spanned(
+                other,
+                format!("Attribute `{}` expects literal as value", name),
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            meta,
+            format!(
+                "Attribute `{}` expects a value, e.g. `{} = ...`",
+                name, name
+            ),
+        )),
+    }
+}
+
+/// Report an attribute not valid in the given position, suggesting the
+/// closest valid attribute.
+pub(crate) fn unrecognized_attribute(meta: &Meta, position: &str, valid: &[&str]) {
+    let path = meta.path();
+    let name = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::");
+    let suggestion =
+        closest_match(&name, valid).map(|suggestion| format!("did you mean `{}`?", suggestion));
+    let valid = valid
+        .iter()
+        .map(|name| format!("`{}`", name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    emit_error!(
+        path, "Unrecognized {} attribute `{}`", position, name;
+        help =? suggestion;
+        note = "valid {} attributes are {}", position, valid;
+    );
+}
+
+fn closest_match<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, candidate)| *distance <= candidate.len().max(3) / 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance counting a transposition of adjacent characters as a
+/// single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    distances[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
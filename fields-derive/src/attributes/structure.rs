@@ -1,139 +1,171 @@
-use std::fmt::Display;
+use fields_case::Case;
+use proc_macro_error2::emit_error;
+use syn::{parse_quote, Attribute, LitStr, Meta, Path};
 
-use proc_macro_error::abort;
-use syn::punctuated::Punctuated;
-use syn::{Attribute, Expr, Lit, Meta, Token};
+use super::field::{insert_meta, parse_meta_entries, MetaLit};
+use super::{
+    attribute_name, parse_fields_attributes, parse_flag, parse_lit_bool, parse_lit_str,
+    parse_profiles, unrecognized_attribute,
+};
 
-#[derive(Default)]
 pub struct StructAttributes {
-    pub rename_all: Option<RenameAll>,
+    pub rename_all: Option<Case>,
+    pub rename_all_profiles: Vec<(String, Case)>,
+    pub rename_all_nested: Option<Case>,
+    pub crate_path: Path,
+    pub redacted_debug: bool,
+    pub nested: bool,
+    pub skip_all: bool,
+    pub transparent: bool,
+    pub remote: Option<Path>,
+    pub register: bool,
+    pub apply_mask: bool,
+    pub meta: Vec<(String, MetaLit)>,
 }
 
-pub enum RenameAll {
-    Lower,
-    Upper,
-    Pascal,
-    Camel,
-    Snake,
-    ScreamingSnake,
-    Kebab,
-    ScreamingKebab,
-}
-
-impl RenameAll {
-    pub fn rename(&self, value: &str) -> String {
-        match self {
-            RenameAll::Lower => value.to_ascii_lowercase().replace(['_', '-'], ""),
-            RenameAll::Upper => value.to_ascii_uppercase().replace(['_', '-'], ""),
-            RenameAll::Pascal => {
-                let mut result = String::new();
-                let mut capitalize = true;
-                for char in value.to_ascii_lowercase().chars() {
-                    if char == '_' {
-                        capitalize = true;
-                    } else if capitalize {
-                        result.push(char.to_ascii_uppercase());
-                        capitalize = false;
-                    } else {
-                        result.push(char);
-                    }
-                }
-                result
-            }
-            RenameAll::Camel => {
-                let pascal = Self::Pascal.rename(value);
-                pascal[..1].to_ascii_lowercase() + &pascal[1..]
-            }
-            RenameAll::Snake => value.to_ascii_lowercase().replace('-', "_"),
-            RenameAll::ScreamingSnake => value.to_ascii_uppercase().replace('-', "_"),
-            RenameAll::Kebab => value.to_ascii_lowercase().replace('_', "-"),
-            RenameAll::ScreamingKebab => value.to_ascii_uppercase().replace('_', "-"),
+impl Default for StructAttributes {
+    fn default() -> Self {
+        Self {
+            rename_all: None,
+            rename_all_profiles: Vec::new(),
+            rename_all_nested: None,
+            crate_path: default_crate_path(),
+            redacted_debug: false,
+            nested: true,
+            skip_all: false,
+            transparent: false,
+            remote: None,
+            register: false,
+            apply_mask: false,
+            meta: Vec::new(),
         }
     }
 }
 
-impl<'a> TryFrom<&'a str> for RenameAll {
-    type Error = RenameAllError<'a>;
-
-    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        match value {
-            "lowercase" => Ok(Self::Lower),
-            "UPPERCASE" => Ok(Self::Upper),
-            "PascalCase" => Ok(Self::Pascal),
-            "camelCase" => Ok(Self::Camel),
-            "snake_case" => Ok(Self::Snake),
-            "SCREAMING_SNAKE_CASE" => Ok(Self::ScreamingSnake),
-            "kebab-case" => Ok(Self::Kebab),
-            "SCREAMING-KEBAB-CASE" => Ok(Self::ScreamingKebab),
-            invalid_value => Err(RenameAllError { invalid_value }),
-        }
+impl StructAttributes {
+    fn rename_all_profile(&mut self, profile: String, rename_all: Case) {
+        self.rename_all_profiles
+            .retain(|(existing, _)| *existing != profile);
+        self.rename_all_profiles.push((profile, rename_all));
     }
 }
 
-#[derive(Debug)]
-pub struct RenameAllError<'a> {
-    invalid_value: &'a str,
+/// Path of the `fields` crate used in generated code unless overridden with
+/// the `crate` attribute.
+pub fn default_crate_path() -> Path {
+    parse_quote!(::fields)
+}
+
+/// Parse a `name = "camelCase"` attribute.
+pub(crate) fn parse_rename_all(meta: &Meta, name: &str) -> syn::Result<Case> {
+    parse_case(&parse_lit_str(meta, name)?, name)
 }
 
-impl<'a> Display for RenameAllError<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Invalid value `{}`. Available options are: `lowercase`, `UPPERCASE`, `PascalCase`, \
-             'camelCase', `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case`, \
-             `SCREAMING-KEBAB-CASE`",
-            self.invalid_value
-        )
+/// Case named by the value of the `name` attribute. Cases producing names
+/// with `.` or spaces are rejected, as such names can't be segments of field
+/// paths.
+pub(crate) fn parse_case(str_lit: &LitStr, name: &str) -> syn::Result<Case> {
+    match Case::from_name(&str_lit.value()) {
+        Some(case) if case.is_path_safe() => Ok(case),
+        Some(case) => Err(syn::Error::new_spanned(
+            str_lit,
+            format!(
+                "`{}` can't be used with `{}`: names containing `.` or spaces can't be segments of field paths",
+                case.name(),
+                name
+            ),
+        )),
+        None => {
+            let options: Vec<String> = Case::ALL
+                .iter()
+                .filter(|case| case.is_path_safe())
+                .map(|case| format!("`{}`", case.name()))
+                .collect();
+            Err(syn::Error::new_spanned(
+                str_lit,
+                format!(
+                    "Invalid value `{}`. Available options are: {}",
+                    str_lit.value(),
+                    options.join(", ")
+                ),
+            ))
+        }
     }
 }
 
-impl<'a> std::error::Error for RenameAllError<'a> {}
+/// Attributes valid on a struct or enum.
+const STRUCT_ATTRIBUTES: &[&str] = &[
+    "rename_all",
+    "rename_all_nested",
+    "crate",
+    "redacted_debug",
+    "nested",
+    "skip_all",
+    "transparent",
+    "remote",
+    "register",
+    "apply_mask",
+    "meta",
+];
 
-pub fn parse_struct_attributes(attributes: &Vec<Attribute>) -> StructAttributes {
+/// Attributes of a struct or enum. Invalid attributes are reported and
+/// ignored.
+pub fn parse_struct_attributes(attributes: &[Attribute]) -> StructAttributes {
     let mut attrs = StructAttributes::default();
 
-    for attr in attributes {
-        if attr.path().is_ident("fields") {
-            let nested = attr
-                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
-                .expect("Cannot parse attribute tokens");
-
-            for meta in nested {
-                match meta {
-                    Meta::NameValue(value) if value.path.is_ident("rename_all") => {
-                        if let Expr::Lit(expr_lit) = value.value {
-                            match expr_lit.lit {
-                                Lit::Str(str_lit) => {
-                                    let rename_all: RenameAll =
-                                        match str_lit.value().as_str().try_into() {
-                                            Ok(result) => result,
-                                            Err(e) => abort!(str_lit, e),
-                                        };
-                                    attrs.rename_all = Some(rename_all);
-                                }
-                                _ => {
-                                    abort!(
-                                        expr_lit.lit,
-                                        "Attribute `rename_all` expects string literal as value"
-                                    )
-                                }
-                            }
-                        } else {
-                            abort!(
-                                value.value,
-                                "Attribute `rename_all` expects literal as value"
-                            );
+    for meta in parse_fields_attributes(attributes) {
+        let result = match attribute_name(&meta).as_deref() {
+            Some(name @ "rename_all") if matches!(meta, Meta::List(_)) => {
+                parse_profiles(&meta, name).map(|profiles| {
+                    for (profile, str_lit) in profiles {
+                        match parse_case(&str_lit, name) {
+                            Ok(case) => attrs.rename_all_profile(profile, case),
+                            Err(e) => emit_error!(e),
                         }
                     }
-                    other => {
-                        let ident = other
-                            .path()
-                            .get_ident()
-                            .expect("Cannot get identifier for unrecognized attribute");
-                        abort!(ident, "Unrecognized attribute `{}`", ident)
-                    }
-                }
+                })
+            }
+            Some(name @ "rename_all") => {
+                parse_rename_all(&meta, name).map(|rename_all| attrs.rename_all = Some(rename_all))
+            }
+            Some(name @ "rename_all_nested") => parse_rename_all(&meta, name)
+                .map(|rename_all| attrs.rename_all_nested = Some(rename_all)),
+            Some(name @ "remote") => parse_lit_str(&meta, name).and_then(|str_lit| {
+                let remote = str_lit.parse().map_err(|e| {
+                    syn::Error::new_spanned(&str_lit, format!("Invalid remote type path: {}", e))
+                })?;
+                attrs.remote = Some(remote);
+                Ok(())
+            }),
+            Some(name @ "crate") => parse_lit_str(&meta, name).and_then(|str_lit| {
+                let crate_path = str_lit.parse().map_err(|e| {
+                    syn::Error::new_spanned(&str_lit, format!("Invalid crate path: {}", e))
+                })?;
+                attrs.crate_path = crate_path;
+                Ok(())
+            }),
+            Some(name @ "redacted_debug") => {
+                parse_flag(&meta, name).map(|()| attrs.redacted_debug = true)
+            }
+            Some(name @ "nested") => {
+                parse_lit_bool(&meta, name).map(|bool_lit| attrs.nested = bool_lit.value())
+            }
+            Some(name @ "skip_all") => parse_flag(&meta, name).map(|()| attrs.skip_all = true),
+            Some(name @ "transparent") => {
+                parse_flag(&meta, name).map(|()| attrs.transparent = true)
+            }
+            Some(name @ "register") => parse_flag(&meta, name).map(|()| attrs.register = true),
+            Some(name @ "apply_mask") => parse_flag(&meta, name).map(|()| attrs.apply_mask = true),
+            Some("meta") => {
+                parse_meta_entries(&meta).map(|entries| insert_meta(&mut attrs.meta, entries))
+            }
+            _ => {
+                unrecognized_attribute(&meta, "struct/enum", STRUCT_ATTRIBUTES);
+                Ok(())
             }
+        };
+        if let Err(e) = result {
+            emit_error!(e);
         }
     }
 
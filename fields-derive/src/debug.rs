@@ -0,0 +1,94 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{parse_quote, Data, DeriveInput, Fields, GenericParam};
+
+use crate::attributes::field::parse_field_attributes;
+
+/// `Debug` implementation printing `***` in place of sensitive fields.
+pub(crate) fn derive_redacted_debug(input: &DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    let type_params: Vec<_> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(type_param) => Some(type_param.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for type_param in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#type_param: ::core::fmt::Debug));
+    }
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let arms = match &input.data {
+        Data::Struct(data) => vec![debug_arm(quote!(Self), &name.to_string(), &data.fields)],
+        Data::Enum(en) => en
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                debug_arm(quote!(Self::#ident), &ident.to_string(), &variant.fields)
+            })
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #type_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+}
+
+fn debug_arm(path: TokenStream, name: &str, fields: &Fields) -> TokenStream {
+    let mut patterns = Vec::new();
+    let mut values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let sensitive = parse_field_attributes(&field.attrs).sensitive;
+        if sensitive {
+            patterns.push(quote! { _ });
+            values.push(quote! { &::core::format_args!("***") });
+        } else {
+            let binding = match &field.ident {
+                Some(ident) => format_ident!("__{}", ident.unraw()),
+                None => format_ident!("__{}", i),
+            };
+            patterns.push(quote! { ref #binding });
+            values.push(quote! { #binding });
+        }
+    }
+
+    match fields {
+        Fields::Named(_) => {
+            let idents = fields.iter().map(|field| &field.ident);
+            let names = fields
+                .iter()
+                .filter_map(|field| field.ident.as_ref())
+                .map(|ident| ident.unraw().to_string());
+            quote! {
+                #path { #(#idents: #patterns),* } => f
+                    .debug_struct(#name)
+                    #(.field(#names, #values))*
+                    .finish(),
+            }
+        }
+        Fields::Unnamed(_) => quote! {
+            #path(#(#patterns),*) => f
+                .debug_tuple(#name)
+                #(.field(#values))*
+                .finish(),
+        },
+        Fields::Unit => quote! {
+            #path => f.write_str(#name),
+        },
+    }
+}
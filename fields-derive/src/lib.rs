@@ -2,15 +2,22 @@
 //!
 //! `fields_derive` is a procedural macro crate for `fields`. It's a transient
 //! dependency and should not be used directly.
+mod apply_mask;
 mod attributes;
+mod debug;
 mod parse;
+mod path;
+mod register;
+mod remote;
 
 use attributes::structure::parse_struct_attributes;
 use proc_macro::TokenStream;
-use proc_macro_error::{abort, proc_macro_error};
+use proc_macro_error2::{abort, abort_if_dirty, proc_macro_error};
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput};
 
+use crate::path::FieldPath;
+
 #[doc = include_str!("../../docs/fields.md")]
 #[proc_macro_derive(Fields, attributes(fields))]
 #[proc_macro_error]
@@ -19,46 +26,227 @@ pub fn derive_fields(input: TokenStream) -> TokenStream {
     let name = &input.ident;
     let (impl_generics, type_generics, where_clause) = &input.generics.split_for_impl();
     let struct_attrs = parse_struct_attributes(&input.attrs);
+    let crate_path = &struct_attrs.crate_path;
 
-    let parsed_fields = match &input.data {
-        Data::Struct(data) => parse::parse_fields(&data.fields, &struct_attrs),
-        Data::Enum(en) => parse::parse_enum_variants(&en.variants, &struct_attrs),
+    let fields = match &input.data {
+        Data::Struct(data) if struct_attrs.transparent => {
+            match parse::parse_transparent(&input, &data.fields, &struct_attrs) {
+                Ok(fields) => fields,
+                Err(e) => abort!(e),
+            }
+        }
+        Data::Struct(data) => {
+            let fields = parse::parse_fields(&data.fields, &struct_attrs);
+            quote! { ::core::option::Option::Some(&[#(#fields),*]) }
+        }
+        Data::Enum(_) if struct_attrs.transparent => abort!(
+            input.ident,
+            "Attribute `transparent` is only applicable to structs"
+        ),
+        Data::Enum(en) => {
+            let fields = parse::parse_enum_variants(&en.variants, &struct_attrs);
+            quote! { ::core::option::Option::Some(&[#(#fields),*]) }
+        }
         _ => abort!(
             input,
             "Fields macro is only applicable to named structs or enums"
         ),
     };
+    abort_if_dirty();
+    match &input.data {
+        Data::Struct(data) if struct_attrs.transparent => {
+            parse::check_includes(&data.fields, &struct_attrs)
+        }
+        Data::Struct(data) => {
+            parse::check_includes(&data.fields, &struct_attrs);
+            parse::check_aliases(&data.fields, &struct_attrs)
+        }
+        Data::Enum(en) => {
+            let fields = || en.variants.iter().flat_map(|variant| &variant.fields);
+            parse::check_includes(fields(), &struct_attrs);
+            parse::check_aliases(fields(), &struct_attrs)
+        }
+        _ => {}
+    }
+    abort_if_dirty();
+
+    let debug = if struct_attrs.redacted_debug {
+        debug::derive_redacted_debug(&input)
+    } else {
+        quote! {}
+    };
+
+    let apply_mask = if struct_attrs.apply_mask {
+        match apply_mask::derive_apply_mask(&input, &struct_attrs) {
+            Ok(apply_mask) => apply_mask,
+            Err(e) => abort!(e),
+        }
+    } else {
+        quote! {}
+    };
+
+    let remote = match struct_attrs.remote {
+        Some(ref remote) => remote::derive_remote_check(&input, remote, &struct_attrs),
+        None => quote! {},
+    };
+
+    let register = if struct_attrs.register {
+        match register::derive_register(&input, crate_path) {
+            Ok(register) => register,
+            Err(e) => abort!(e),
+        }
+    } else {
+        quote! {}
+    };
+
+    let doc = match parse::parse_doc(&input.attrs) {
+        Some(doc) => quote! {
+            const DOC: ::core::option::Option<&'static ::core::primitive::str> =
+                ::core::option::Option::Some(#doc);
+        },
+        None => quote! {},
+    };
+
+    let meta = if struct_attrs.meta.is_empty() {
+        quote! {}
+    } else {
+        let entries = struct_attrs.meta.iter().map(|(key, value)| {
+            let value = value.to_tokens(crate_path);
+            quote! { (#key, #value) }
+        });
+        quote! {
+            const META: &'static [(&'static ::core::primitive::str, #crate_path::MetaValue)] =
+                &[#(#entries),*];
+        }
+    };
+
+    let field_names = if input.generics.params.is_empty() {
+        quote! {
+            impl #crate_path::FieldNames for #name {
+                const FIELD_NAMES: &'static [&'static ::core::primitive::str] = {
+                    const FIELDS: ::core::option::Option<&'static [#crate_path::Field]> =
+                        <#name as #crate_path::Fields>::FIELDS;
+                    const LEN: ::core::primitive::usize = #crate_path::__private::paths_len(FIELDS);
+                    const BYTES: &[::core::primitive::u8; LEN] =
+                        &#crate_path::__private::path_bytes::<LEN>(FIELDS);
+                    const COUNT: ::core::primitive::usize =
+                        #crate_path::__private::unique_paths_count(BYTES);
+                    const NAMES: [&::core::primitive::str; COUNT] =
+                        #crate_path::__private::unique_paths::<COUNT, LEN>(BYTES);
+                    #crate_path::__private::check_field_names(FIELDS);
+                    &NAMES
+                };
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Paths of types without generic parameters are computed at compile time,
+    // unless a field has a provider, which can only be called at runtime. A
+    // `static` in a generic function is shared by all instantiations, so
+    // generic types cache the paths of each instantiation separately.
+    let lazy_fields = quote! {
+        static INSTANCE: #crate_path::__private::Lazy<::core::option::Option<#crate_path::FieldNameSet>> =
+            #crate_path::__private::Lazy::new();
+        INSTANCE.get_or_init(|| #crate_path::__private::field_names(<Self as #crate_path::Fields>::FIELDS))
+    };
+    let fields_body = if input.generics.params.is_empty() {
+        quote! {
+            const FIELDS: ::core::option::Option<&'static [#crate_path::Field]> =
+                <#name as #crate_path::Fields>::FIELDS;
+            if #crate_path::__private::has_provider(FIELDS) {
+                #lazy_fields
+            } else {
+                const LEN: ::core::primitive::usize = #crate_path::__private::paths_len(FIELDS);
+                const BYTES: &[::core::primitive::u8; LEN] =
+                    &#crate_path::__private::path_bytes::<LEN>(FIELDS);
+                const COUNT: ::core::primitive::usize =
+                    #crate_path::__private::unique_paths_count(BYTES);
+                const PATHS: [&::core::primitive::str; COUNT] =
+                    #crate_path::__private::sorted_unique_paths::<COUNT, LEN>(BYTES);
+                static NAMES: ::core::option::Option<#crate_path::FieldNameSet> =
+                    #crate_path::__private::static_field_names(FIELDS, &PATHS);
+                &NAMES
+            }
+        }
+    } else {
+        quote! {
+            static INSTANCES: #crate_path::__private::GenericFieldNames =
+                #crate_path::__private::GenericFieldNames::new();
+            INSTANCES.get(<Self as #crate_path::Fields>::FIELDS)
+        }
+    };
 
     let result = quote! {
-        impl #impl_generics ::fields::Fields for #name #type_generics #where_clause {
-            fn fields() -> &'static Option<std::collections::HashSet<String>> {
-                static INSTANCE: ::fields::OnceCell<Option<std::collections::HashSet<String>>> = ::fields::OnceCell::new();
-                INSTANCE.get_or_init(|| {
-                    let mut field_names = std::collections::HashSet::new();
-                    #({
-                        let (field_name, optional_fields): (Option<&str>, &Option<std::collections::HashSet<String>>) = #parsed_fields;
-                        match (field_name, optional_fields) {
-                            (Some(name), Some(fields)) => {
-                                field_names.extend(
-                                    fields.iter().map(|field| format!("{}.{}", name, field))
-                                );
-                            },
-                            (None, Some(fields)) => {
-                                field_names.extend(
-                                    fields.iter().map(|field| format!("{}", field))
-                                );
-                            }
-                            (Some(name), None) => {
-                                field_names.insert(name.to_string());
-                            }
-                            (None, None) => {}
-                        }
-                    })*
-                    Some(field_names)
-                })
+        impl #impl_generics #crate_path::Fields for #name #type_generics #where_clause {
+            const FIELDS: ::core::option::Option<&'static [#crate_path::Field]> = #fields;
+            const RUNTIME_FIELDS: ::core::primitive::bool = false;
+
+            #doc
+
+            #meta
+
+            fn fields() -> &'static ::core::option::Option<#crate_path::FieldNameSet> {
+                #fields_body
             }
         }
+
+        #field_names
+
+        #debug
+
+        #apply_mask
+
+        #remote
+
+        #register
     };
 
     TokenStream::from(result)
 }
+
+/// Checked field path.
+///
+/// Expands to the `&'static str` path of a field of a type deriving
+/// [Fields], as it appears in its `fields()`. Segments are field names after
+/// renames, given as identifiers or string literals. Compilation fails if
+/// any segment of the path does not exist on the type. When used through a
+/// crate re-exporting `fields`, pass its path first, e.g.
+/// `path!(crate = "facade::fields", User, address)`.
+///
+/// ```rust
+/// use fields::Fields;
+///
+/// #[derive(Fields)]
+/// struct User {
+///     #[fields(rename = "home")]
+///     address: Address,
+/// }
+///
+/// #[derive(Fields)]
+/// #[fields(rename_all = "camelCase")]
+/// struct Address {
+///     street_name: String,
+/// }
+///
+/// assert_eq!(fields::path!(User, home.streetName), "home.streetName");
+/// ```
+///
+/// ```compile_fail
+/// use fields::Fields;
+///
+/// #[derive(Fields)]
+/// struct User {
+///     address: String,
+/// }
+///
+/// // `User` has no field path `address.city`: segment `city` does not exist
+/// let path = fields::path!(User, address.city);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn path(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as FieldPath);
+    TokenStream::from(path.expand())
+}
@@ -1,9 +1,15 @@
+use fields_case::Case;
+use proc_macro2::TokenStream;
+use proc_macro_error2::emit_error;
 use quote::quote;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{Fields, Ident, Type, Variant};
+use syn::{
+    Attribute, DeriveInput, Expr, ExprLit, Fields, Ident, Lit, Meta, MetaNameValue, Path, Type,
+    Variant,
+};
 
-use crate::attributes::field::parse_field_attributes;
+use crate::attributes::field::{insert_meta, parse_field_attributes, FieldAttributes, With};
 use crate::attributes::structure::StructAttributes;
 
 pub(crate) fn parse_enum_variants(
@@ -12,8 +18,7 @@ pub(crate) fn parse_enum_variants(
 ) -> Vec<proc_macro2::TokenStream> {
     let mut token_stream = Vec::new();
     for variant in variants.iter() {
-        let parsed_fields = parse_fields(&variant.fields, struct_attrs);
-        token_stream.extend(parsed_fields);
+        token_stream.extend(parse_fields(&variant.fields, struct_attrs));
     }
     token_stream
 }
@@ -22,54 +27,338 @@ pub(crate) fn parse_fields(
     fields: &Fields,
     struct_attrs: &StructAttributes,
 ) -> Vec<proc_macro2::TokenStream> {
+    let crate_path = &struct_attrs.crate_path;
     let mut field_exprs = Vec::new();
 
     for field in fields.iter() {
-        let field_name = field.ident.as_ref().map(|i| {
-            if let Some(ref rename_all) = struct_attrs.rename_all {
-                rename_all.rename(&sanitize_name(i))
-            } else {
-                sanitize_name(i)
-            }
-        });
         let field_type = &field.ty;
         let field_attrs = parse_field_attributes(&field.attrs);
+        let field_name = field
+            .ident
+            .as_ref()
+            .map(|ident| field_name(ident, &field_attrs, struct_attrs));
 
         let nested_struct = match field_type {
             Type::Path(type_path) => Some(type_path),
             _ => None,
         };
 
-        if !field_attrs.skip {
-            match (field_name, nested_struct) {
-                (Some(name), Some(type_path)) => {
-                    let name = field_attrs.rename.unwrap_or(name);
-                    let fields = if field_attrs.nested {
-                        quote! { <#type_path as ::fields::Fields>::fields() }
+        if !is_skipped(&field_attrs, struct_attrs) {
+            let mut field_expr = match (&field.ident, field_name) {
+                (Some(ident), Some(name)) => {
+                    let profiles = profile_names(ident, &name, &field_attrs, struct_attrs);
+                    let mut field_expr = quote! { #crate_path::Field::named(#name) };
+                    let ident = sanitize_name(ident);
+                    if ident != name {
+                        field_expr = quote! { #field_expr.with_ident(#ident) };
+                    }
+                    if field_attrs.rename.is_some() {
+                        field_expr = quote! { #field_expr.with_renamed(true) };
                     } else {
-                        quote! { &None }
-                    };
-
-                    field_exprs.push(quote! { (Some(#name), #fields) })
-                }
-                (None, Some(type_path)) => {
-                    let fields = if field_attrs.nested {
-                        quote! { <#type_path as ::fields::Fields>::fields() }
+                        let cased_names = Case::ALL
+                            .iter()
+                            .filter(|case| case.is_path_safe())
+                            .map(|case| case.apply(&ident));
+                        field_expr = quote! { #field_expr.with_cased_names(&[#(#cased_names),*]) };
+                    }
+                    if profiles.is_empty() {
+                        field_expr
                     } else {
-                        quote! { &None }
-                    };
+                        let entries = profiles
+                            .iter()
+                            .map(|(profile, name)| quote! { (#profile, #name) });
+                        quote! { #field_expr.with_profiles(&[#(#entries),*]) }
+                    }
+                }
+                _ => quote! { #crate_path::Field::unnamed() },
+            };
+            let rename_all_nested = field_attrs
+                .rename_all_nested
+                .as_ref()
+                .or(struct_attrs.rename_all_nested.as_ref());
+            if let Some(rename_all) = rename_all_nested {
+                let case = case_tokens(*rename_all, crate_path);
+                field_expr = quote! { #field_expr.with_nested_case(#case) };
+            }
+            if !field_attrs.groups.is_empty() {
+                let groups = &field_attrs.groups;
+                field_expr = quote! { #field_expr.with_groups(&[#(#groups),*]) };
+            }
+            if !field_attrs.aliases.is_empty() {
+                let aliases = &field_attrs.aliases;
+                field_expr = quote! { #field_expr.with_aliases(&[#(#aliases),*]) };
+            }
+            if let Some(ref reason) = field_attrs.deprecated {
+                field_expr = quote! { #field_expr.with_deprecated(#reason) };
+            }
+            if let Some(ref version) = field_attrs.since {
+                field_expr = quote! { #field_expr.with_since(#version) };
+            }
+            if let Some(ref version) = field_attrs.until {
+                field_expr = quote! { #field_expr.with_until(#version) };
+            }
+            if field_attrs.sensitive {
+                field_expr = quote! { #field_expr.with_sensitive(true) };
+            }
+            let mut meta = struct_attrs.meta.clone();
+            insert_meta(&mut meta, field_attrs.meta);
+            if !meta.is_empty() {
+                let entries = meta.iter().map(|(key, value)| {
+                    let value = value.to_tokens(crate_path);
+                    quote! { (#key, #value) }
+                });
+                field_expr = quote! { #field_expr.with_meta(&[#(#entries),*]) };
+            }
+            if let Some(doc) = parse_doc(&field.attrs) {
+                field_expr = quote! { #field_expr.with_doc(#doc) };
+            }
+
+            let nested = field_attrs.nested.unwrap_or(struct_attrs.nested);
+            match (&field_attrs.with, nested_struct) {
+                (Some(With::Type(with)), _) if nested => field_exprs.push(quote! {
+                    #field_expr.with_nested_type::<#with>()
+                }),
+                (Some(With::Provider(provider)), _) if nested => field_exprs.push(quote! {
+                    #field_expr.with_provider(#provider)
+                }),
+                (None, Some(type_path)) if nested => field_exprs.push(quote! {
+                    #field_expr.with_nested_type::<#type_path>()
+                }),
+                _ => field_exprs.push(field_expr),
+            }
+        }
+    }
+    field_exprs
+}
 
-                    field_exprs.push(quote! { (None, #fields) })
+/// Fields of a transparent struct: the fields of the type of its only
+/// non-skipped field, or of its `with` type, or none if the field is not
+/// nested.
+pub(crate) fn parse_transparent(
+    input: &DeriveInput,
+    fields: &Fields,
+    struct_attrs: &StructAttributes,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let crate_path = &struct_attrs.crate_path;
+    let mut included = Vec::new();
+    for field in fields.iter() {
+        let field_attrs = parse_field_attributes(&field.attrs);
+        if !is_skipped(&field_attrs, struct_attrs) {
+            included.push((field, field_attrs));
+        }
+    }
+
+    match included.as_slice() {
+        [(field, field_attrs)] => {
+            let nested = field_attrs.nested.unwrap_or(struct_attrs.nested);
+            match (&field_attrs.with, &field.ty) {
+                (Some(With::Type(with)), _) if nested => {
+                    Ok(quote! { <#with as #crate_path::Fields>::FIELDS })
                 }
-                (Some(name), None) => {
-                    let name = field_attrs.rename.unwrap_or(name);
-                    field_exprs.push(quote! { (Some(#name), None) })
+                (Some(With::Provider(provider)), _) if nested => Err(syn::Error::new_spanned(
+                    provider,
+                    "Attribute `transparent` does not support `with` functions",
+                )),
+                (None, Type::Path(type_path)) if nested => {
+                    Ok(quote! { <#type_path as #crate_path::Fields>::FIELDS })
                 }
-                (None, None) => {}
+                _ => Ok(quote! { ::core::option::Option::None }),
             }
         }
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            format!(
+                "Attribute `transparent` requires exactly one non-skipped field, found {}",
+                included.len()
+            ),
+        )),
     }
-    field_exprs
+}
+
+/// Report aliases that are the name or an alias of another of the `fields`
+/// of a struct or of all variants of an enum, which would make paths using
+/// them ambiguous.
+pub(crate) fn check_aliases<'a>(
+    fields: impl IntoIterator<Item = &'a syn::Field>,
+    struct_attrs: &StructAttributes,
+) {
+    let named: Vec<(String, FieldAttributes)> = fields
+        .into_iter()
+        .filter_map(|field| {
+            let field_attrs = parse_field_attributes(&field.attrs);
+            if is_skipped(&field_attrs, struct_attrs) {
+                return None;
+            }
+            let name = field_name(field.ident.as_ref()?, &field_attrs, struct_attrs);
+            Some((name, field_attrs))
+        })
+        .collect();
+
+    for (name, field_attrs) in &named {
+        for alias in &field_attrs.aliases {
+            let alias_value = alias.value();
+            let other = named.iter().find(|(other, other_attrs)| {
+                other != name
+                    && (*other == alias_value
+                        || other_attrs
+                            .aliases
+                            .iter()
+                            .any(|other_alias| other_alias.value() == alias_value))
+            });
+            if let Some((other, _)) = other {
+                emit_error!(
+                    alias,
+                    "Alias `{}` of field `{}` is already the name or an alias of field `{}`",
+                    alias_value,
+                    name,
+                    other
+                );
+            }
+        }
+    }
+}
+
+/// Reports `include` on fields of containers without `skip_all`, where it has
+/// no effect.
+pub(crate) fn check_includes<'a>(
+    fields: impl IntoIterator<Item = &'a syn::Field>,
+    struct_attrs: &StructAttributes,
+) {
+    if struct_attrs.skip_all {
+        return;
+    }
+    for field in fields {
+        if let Some(include) = parse_field_attributes(&field.attrs).include {
+            emit_error!(
+                include,
+                "Attribute `include` has no effect without `skip_all` on the container";
+                help = "add `#[fields(skip_all)]` to the container or remove `include`"
+            );
+        }
+    }
+}
+
+pub(crate) fn is_skipped(field_attrs: &FieldAttributes, struct_attrs: &StructAttributes) -> bool {
+    field_attrs.skip || struct_attrs.skip_all && field_attrs.include.is_none()
+}
+
+/// Name of a named field after `rename` or the `rename_all` of its container.
+fn field_name(
+    ident: &Ident,
+    field_attrs: &FieldAttributes,
+    struct_attrs: &StructAttributes,
+) -> String {
+    if let Some(rename) = &field_attrs.rename {
+        return rename.clone();
+    }
+    let rename_all = struct_attrs
+        .rename_all
+        .as_ref()
+        .or(struct_attrs.rename_all_nested.as_ref());
+    match rename_all {
+        Some(rename_all) => rename_all.apply(&sanitize_name(ident)),
+        None => sanitize_name(ident),
+    }
+}
+
+/// Names of a field in the naming profiles of the field and its container
+/// that differ from its default `name`. A profile rename of the field takes
+/// precedence over a plain rename, which takes precedence over the profile
+/// `rename_all` of the container.
+fn profile_names(
+    ident: &Ident,
+    name: &str,
+    field_attrs: &FieldAttributes,
+    struct_attrs: &StructAttributes,
+) -> Vec<(String, String)> {
+    let mut profiles: Vec<&String> = field_attrs
+        .rename_profiles
+        .iter()
+        .map(|(profile, _)| profile)
+        .chain(
+            struct_attrs
+                .rename_all_profiles
+                .iter()
+                .map(|(profile, _)| profile),
+        )
+        .collect();
+    profiles.sort();
+    profiles.dedup();
+
+    profiles
+        .into_iter()
+        .filter_map(|profile| {
+            let renamed = field_attrs
+                .rename_profiles
+                .iter()
+                .find(|(candidate, _)| candidate == profile)
+                .map(|(_, renamed)| renamed.clone())
+                .or_else(|| field_attrs.rename.clone())
+                .or_else(|| {
+                    struct_attrs
+                        .rename_all_profiles
+                        .iter()
+                        .find(|(candidate, _)| candidate == profile)
+                        .map(|(_, rename_all)| rename_all.apply(&sanitize_name(ident)))
+                })?;
+            (renamed != name).then(|| (profile.clone(), renamed))
+        })
+        .collect()
+}
+
+/// Text of the doc comments among `attributes`, with the leading space of
+/// each line removed, `None` if there are none.
+pub(crate) fn parse_doc(attributes: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attributes
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(str_lit),
+                        ..
+                    }),
+                ..
+            }) => Some(str_lit.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.split('\n')
+                .map(|line| {
+                    line.strip_prefix(' ')
+                        .unwrap_or(line)
+                        .trim_end()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let doc = lines.join("\n").trim().to_string();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
+/// Path of the `Case` variant `case` in generated code.
+fn case_tokens(case: Case, crate_path: &Path) -> TokenStream {
+    let variant = match case {
+        Case::Lower => quote! { Lower },
+        Case::Upper => quote! { Upper },
+        Case::Pascal => quote! { Pascal },
+        Case::Camel => quote! { Camel },
+        Case::Snake => quote! { Snake },
+        Case::ScreamingSnake => quote! { ScreamingSnake },
+        Case::Kebab => quote! { Kebab },
+        Case::ScreamingKebab => quote! { ScreamingKebab },
+        Case::Train => quote! { Train },
+        Case::Dot => quote! { Dot },
+        Case::Title => quote! { Title },
+    };
+    quote! { #crate_path::Case::#variant }
 }
 
 fn sanitize_name(ident: &Ident) -> String {
@@ -0,0 +1,89 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Path, Token, Type};
+
+use crate::attributes::structure::default_crate_path;
+
+/// Input of the `path!` macro: an optional `crate = "path"` argument, a type
+/// and a dotted field path.
+pub(crate) struct FieldPath {
+    crate_path: Path,
+    ty: Type,
+    segments: Vec<String>,
+}
+
+impl Parse for FieldPath {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let crate_path = if input.peek(Token![crate]) && input.peek2(Token![=]) {
+            input.parse::<Token![crate]>()?;
+            input.parse::<Token![=]>()?;
+            let crate_path = input.parse::<LitStr>()?.parse()?;
+            input.parse::<Token![,]>()?;
+            crate_path
+        } else {
+            default_crate_path()
+        };
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let mut segments = vec![parse_segment(input)?];
+        while input.peek(Token![.]) {
+            input.parse::<Token![.]>()?;
+            segments.push(parse_segment(input)?);
+        }
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(Self {
+            crate_path,
+            ty,
+            segments,
+        })
+    }
+}
+
+fn parse_segment(input: ParseStream) -> syn::Result<String> {
+    if input.peek(LitStr) {
+        Ok(input.parse::<LitStr>()?.value())
+    } else if input.peek(Ident::peek_any) {
+        Ok(Ident::parse_any(input)?.unraw().to_string())
+    } else {
+        Err(input.error("expected field name"))
+    }
+}
+
+impl FieldPath {
+    pub(crate) fn expand(&self) -> TokenStream {
+        let crate_path = &self.crate_path;
+        let ty = &self.ty;
+        let type_name = ty.to_token_stream().to_string().replace(' ', "");
+        let path = self.segments.join(".");
+
+        let checks = self.segments.iter().map(|segment| {
+            let error = format!(
+                "`{}` has no field path `{}`: segment `{}` does not exist",
+                type_name, path, segment
+            );
+            quote! {
+                let fields = #crate_path::__private::path_segment(fields, #segment, #error);
+            }
+        });
+
+        quote! {
+            {
+                const PATH: &'static ::core::primitive::str = {
+                    let fields = #crate_path::__private::path_start(
+                        <#ty as #crate_path::Fields>::FIELDS,
+                    );
+                    #(#checks)*
+                    let _ = fields;
+                    #path
+                };
+                PATH
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Path};
+
+/// Constructor adding the type to the process-wide registry when the program
+/// starts. The function is placed in the section of initializers the platform
+/// runs before `main`, so no runtime support beyond the loader is needed.
+/// Targets without such a section or without the atomics the registry needs
+/// fail to compile.
+pub(crate) fn derive_register(input: &DeriveInput, crate_path: &Path) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Attribute `register` is not supported for generic types",
+        ));
+    }
+    let name = &input.ident;
+    let type_name = name.to_string();
+    let elf = quote! {
+        any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "dragonfly",
+            target_os = "illumos",
+            target_os = "solaris",
+        )
+    };
+    let supported = quote! {
+        all(target_has_atomic = "ptr", any(#elf, target_vendor = "apple", windows))
+    };
+
+    Ok(quote! {
+        #[cfg(not(#supported))]
+        ::core::compile_error!(
+            "Attribute `register` is only supported on ELF, Apple and Windows targets with pointer-sized atomics"
+        );
+
+        #[cfg(#supported)]
+        const _: () = {
+            static REGISTRATION: #crate_path::__private::Registration =
+                #crate_path::__private::Registration::new(
+                    || #crate_path::TypeFields::named::<#name>(#type_name),
+                    ::core::module_path!(),
+                );
+
+            #[used]
+            #[cfg_attr(#elf, unsafe(link_section = ".init_array"))]
+            #[cfg_attr(target_vendor = "apple", unsafe(link_section = "__DATA,__mod_init_func"))]
+            #[cfg_attr(windows, unsafe(link_section = ".CRT$XCU"))]
+            static REGISTER: extern "C" fn() = {
+                extern "C" fn register() {
+                    #crate_path::__private::register(&REGISTRATION);
+                }
+                register
+            };
+        };
+    })
+}
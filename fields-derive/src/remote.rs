@@ -0,0 +1,71 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, Index, Member, Path, PathArguments};
+
+use crate::attributes::field::parse_field_attributes;
+use crate::attributes::structure::StructAttributes;
+use crate::parse::is_skipped;
+
+/// Function matching a value of the `remote` type against the fields of its
+/// mirror definition, so that compilation fails when a field of the mirror is
+/// missing from the remote type. Patterns name only the fields of the mirror
+/// that aren't skipped and end with `..`, so that `#[non_exhaustive]` types
+/// and types with private fields can be mirrored, and errors point at the
+/// fields of the mirror.
+pub(crate) fn derive_remote_check(
+    input: &DeriveInput,
+    remote: &Path,
+    struct_attrs: &StructAttributes,
+) -> TokenStream {
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+    let mut pattern_path = remote.clone();
+    for segment in pattern_path.segments.iter_mut() {
+        segment.arguments = PathArguments::None;
+    }
+
+    let arms = match &input.data {
+        Data::Struct(data) => vec![remote_arm(
+            quote!(#pattern_path),
+            &data.fields,
+            struct_attrs,
+        )],
+        Data::Enum(en) => en
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                remote_arm(quote!(#pattern_path::#ident), &variant.fields, struct_attrs)
+            })
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+
+    quote! {
+        const _: () = {
+            #[allow(dead_code)]
+            fn check #impl_generics (value: &#remote) #where_clause {
+                match *value {
+                    #(#arms)*
+                    #[allow(unreachable_patterns)]
+                    _ => {}
+                }
+            }
+        };
+    }
+}
+
+fn remote_arm(path: TokenStream, fields: &Fields, struct_attrs: &StructAttributes) -> TokenStream {
+    let members = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !is_skipped(&parse_field_attributes(&field.attrs), struct_attrs))
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index {
+                index: index as u32,
+                span: field.span(),
+            }),
+        });
+    quote! { #path { #(#members: _,)* .. } => {} }
+}
@@ -1,16 +1,16 @@
 #![allow(dead_code)]
+#![allow(clippy::useless_vec)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-use fields::Fields;
+use fields::{FieldNames, FieldSet, Fields};
 
 fn get_fields<T: Fields>() -> Vec<&'static str> {
-    T::fields()
-        .as_ref()
-        .unwrap()
-        .iter()
-        .map(|f| f.as_str())
-        .collect()
+    T::fields().as_ref().unwrap().iter().collect()
+}
+
+fn paths(fields: Option<FieldSet>) -> Vec<String> {
+    fields.unwrap().into_iter().collect()
 }
 
 fn assert_fields(expected: &[&str], fields: &[&str]) {
@@ -288,3 +288,1653 @@ fn renaming_all_fields_is_working() {
         &fields,
     );
 }
+
+#[test]
+fn checked_paths_are_expanded() {
+    #[derive(Fields)]
+    #[fields(rename_all = "camelCase")]
+    struct User {
+        user_name: String,
+        #[fields(rename = "addresses")]
+        address: Vec<Address>,
+        kind: Kind,
+    }
+
+    #[derive(Fields)]
+    struct Address {
+        city: String,
+        r#type: String,
+    }
+
+    #[derive(Fields)]
+    enum Kind {
+        Person { first_name: String },
+        Company(Company),
+    }
+
+    #[derive(Fields)]
+    struct Company {
+        tax_id: String,
+    }
+
+    assert_eq!(fields::path!(User, userName), "userName");
+    assert_eq!(fields::path!(User, addresses), "addresses");
+    assert_eq!(fields::path!(User, addresses.city), "addresses.city");
+    assert_eq!(fields::path!(User, addresses.r#type), "addresses.type");
+    assert_eq!(fields::path!(User, "addresses"."city"), "addresses.city");
+    assert_eq!(fields::path!(User, kind.first_name), "kind.first_name");
+    assert_eq!(fields::path!(User, kind.tax_id), "kind.tax_id");
+}
+
+#[test]
+fn manual_implementations_only_need_fields() {
+    struct Manual;
+
+    impl Fields for Manual {
+        fn fields() -> &'static Option<fields::FieldNameSet> {
+            &None
+        }
+    }
+
+    assert!(Manual::FIELDS.is_none());
+    assert!(Manual::fields().is_none());
+}
+
+#[test]
+fn nested_manual_implementations_provide_their_fields() {
+    struct Geo;
+
+    impl Fields for Geo {
+        fn fields() -> &'static Option<fields::FieldNameSet> {
+            static FIELDS: std::sync::OnceLock<Option<FieldSet>> = std::sync::OnceLock::new();
+            FIELDS.get_or_init(|| Some(["lat", "lon"].into_iter().collect()))
+        }
+    }
+
+    #[derive(Fields)]
+    struct Test {
+        id: i32,
+        geo: Geo,
+        history: Vec<Geo>,
+        #[fields(nested = false)]
+        origin: Geo,
+    }
+
+    assert_eq!(
+        get_fields::<Test>(),
+        [
+            "geo.lat",
+            "geo.lon",
+            "history.lat",
+            "history.lon",
+            "id",
+            "origin"
+        ]
+    );
+    assert!(Test::field("geo").unwrap().provider().is_some());
+}
+
+#[test]
+fn field_descriptions_are_available_in_const_context() {
+    #[derive(Fields)]
+    struct Test {
+        a: i32,
+        #[fields(skip)]
+        b: i32,
+        c: Nested,
+    }
+
+    #[derive(Fields)]
+    struct Nested {
+        d: i32,
+    }
+
+    const FIELDS: &[fields::Field] = match Test::FIELDS {
+        Some(fields) => fields,
+        None => &[],
+    };
+
+    assert_eq!(FIELDS.len(), 2);
+    assert_eq!(FIELDS[0].name(), Some("a"));
+    assert!(FIELDS[0].nested().is_none());
+    assert_eq!(FIELDS[1].name(), Some("c"));
+    assert_eq!(FIELDS[1].nested().unwrap()[0].name(), Some("d"));
+}
+
+#[test]
+fn const_field_names_match_fields() {
+    #[derive(Fields)]
+    #[fields(rename_all = "camelCase")]
+    struct Test {
+        first_name: String,
+        #[fields(skip)]
+        skipped: i32,
+        nested: Vec<Nested>,
+        #[fields(nested = false)]
+        not_nested: Nested,
+        variant: TestEnum,
+    }
+
+    #[derive(Fields)]
+    struct Nested {
+        a: i32,
+        b: Option<Box<Level2>>,
+    }
+
+    #[derive(Fields)]
+    struct Level2 {
+        c: i32,
+    }
+
+    #[derive(Fields)]
+    enum TestEnum {
+        Variant1 { d: i32 },
+        Variant2 { d: i32, e: i32 },
+        Variant3(Level2),
+    }
+
+    const NAMES: &[&str] = <Test as FieldNames>::FIELD_NAMES;
+    let lengths = [0; <Test as FieldNames>::FIELD_NAMES.len()];
+
+    assert_eq!(
+        NAMES,
+        [
+            "firstName",
+            "nested.a",
+            "nested.b.c",
+            "notNested",
+            "variant.d",
+            "variant.e",
+            "variant.c"
+        ]
+    );
+    assert_eq!(lengths.len(), 7);
+    assert_eq!(
+        NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<FieldSet>(),
+        *Test::fields().as_ref().unwrap()
+    );
+}
+
+#[test]
+fn generic_instantiations_have_their_own_fields() {
+    #[derive(Fields)]
+    struct A {
+        a: i32,
+    }
+
+    #[derive(Fields)]
+    struct B {
+        b: i32,
+    }
+
+    #[derive(Fields)]
+    struct Wrapper<T: Fields> {
+        inner: T,
+    }
+
+    fn inner_fields<T: Fields>() -> Option<FieldSet> {
+        T::fields().clone()
+    }
+
+    #[derive(Fields)]
+    struct Provided<T: Fields> {
+        #[fields(with_fn = "inner_fields::<T>")]
+        inner: T,
+    }
+
+    assert_eq!(get_fields::<Wrapper<A>>(), ["inner.a"]);
+    assert_eq!(get_fields::<Wrapper<B>>(), ["inner.b"]);
+    assert_eq!(get_fields::<Wrapper<Wrapper<A>>>(), ["inner.inner.a"]);
+    assert_eq!(get_fields::<Wrapper<A>>(), ["inner.a"]);
+
+    assert_eq!(get_fields::<Provided<Provided<A>>>(), ["inner.inner.a"]);
+    assert_eq!(get_fields::<Provided<A>>(), ["inner.a"]);
+    assert_eq!(get_fields::<Provided<B>>(), ["inner.b"]);
+}
+
+#[allow(non_camel_case_types, unused_macros)]
+mod shadowed_prelude {
+    pub type Option<T> = ::core::result::Result<T, ()>;
+    pub type Result<T> = ::core::option::Option<T>;
+    pub struct Some;
+    pub struct None;
+    pub struct String;
+    pub struct usize;
+    pub struct u8;
+    pub struct str;
+
+    pub mod std {}
+    pub mod core {}
+
+    macro_rules! format {
+        () => {};
+    }
+
+    #[derive(::fields::Fields)]
+    pub struct Test {
+        pub a: i32,
+        pub nested: Nested,
+    }
+
+    #[derive(::fields::Fields)]
+    pub struct Nested {
+        pub b: i32,
+    }
+
+    #[derive(::fields::Fields)]
+    pub enum TestEnum {
+        Variant(Nested),
+    }
+
+    pub const PATH: &::core::primitive::str = ::fields::path!(Test, nested.b);
+}
+
+mod no_implicit_prelude {
+    #![no_implicit_prelude]
+
+    #[derive(::fields::Fields)]
+    pub struct Test {
+        pub a: i32,
+        pub nested: Nested,
+    }
+
+    #[derive(::fields::Fields)]
+    pub struct Nested {
+        pub b: i32,
+    }
+}
+
+#[test]
+fn generated_code_survives_shadowed_prelude() {
+    assert_fields(&["a", "nested.b"], &get_fields::<shadowed_prelude::Test>());
+    assert_fields(&["b"], &get_fields::<shadowed_prelude::TestEnum>());
+    assert_eq!(
+        <shadowed_prelude::Test as FieldNames>::FIELD_NAMES,
+        ["a", "nested.b"]
+    );
+    assert_eq!(shadowed_prelude::PATH, "nested.b");
+}
+
+#[test]
+fn generated_code_works_without_implicit_prelude() {
+    assert_fields(
+        &["a", "nested.b"],
+        &get_fields::<no_implicit_prelude::Test>(),
+    );
+    assert_eq!(
+        <no_implicit_prelude::Test as FieldNames>::FIELD_NAMES,
+        ["a", "nested.b"]
+    );
+}
+
+mod facade {
+    pub use ::fields;
+}
+
+#[test]
+fn crate_path_can_be_configured() {
+    #[derive(facade::fields::Fields)]
+    #[fields(crate = "crate::facade::fields")]
+    struct Test {
+        a: i32,
+        nested: Nested,
+    }
+
+    #[derive(facade::fields::Fields)]
+    #[fields(crate = "crate::facade::fields", rename_all = "UPPERCASE")]
+    struct Nested {
+        b: i32,
+    }
+
+    assert_fields(&["a", "nested.B"], &get_fields::<Test>());
+    assert_eq!(<Test as FieldNames>::FIELD_NAMES, ["a", "nested.B"]);
+    assert_eq!(
+        facade::fields::path!(crate = "crate::facade::fields", Test, nested.B),
+        "nested.B"
+    );
+}
+
+#[test]
+fn fields_in_group_are_selected() {
+    #[derive(Fields)]
+    struct User {
+        #[fields(group = "public", group = "admin")]
+        name: String,
+        #[fields(group = "admin")]
+        email: String,
+        password: String,
+        #[fields(group = "public", group = "admin")]
+        profile: Profile,
+        #[fields(group = "admin")]
+        address: Address,
+    }
+
+    #[derive(Fields)]
+    struct Profile {
+        #[fields(group = "public")]
+        bio: String,
+        #[fields(group = "admin")]
+        notes: String,
+    }
+
+    #[derive(Fields)]
+    struct Address {
+        city: String,
+        street: String,
+    }
+
+    assert_eq!(
+        paths(User::fields_in_group("public")),
+        ["name", "profile.bio"]
+    );
+    assert_eq!(
+        paths(User::fields_in_group("admin")),
+        [
+            "address.city",
+            "address.street",
+            "email",
+            "name",
+            "profile.notes"
+        ]
+    );
+    assert!(paths(User::fields_in_group("internal")).is_empty());
+}
+
+#[test]
+fn aliases_resolve_to_canonical_paths() {
+    #[derive(Fields)]
+    struct Test {
+        #[fields(alias = "userName", alias = "login")]
+        user_name: String,
+        #[fields(rename = "home", alias = "address")]
+        home_address: Vec<Address>,
+        kind: Kind,
+    }
+
+    #[derive(Fields)]
+    struct Address {
+        #[fields(alias = "town")]
+        city: String,
+    }
+
+    #[derive(Fields)]
+    enum Kind {
+        Person(Person),
+    }
+
+    #[derive(Fields)]
+    struct Person {
+        #[fields(alias = "first")]
+        first_name: String,
+    }
+
+    assert_fields(
+        &["user_name", "home.city", "kind.first_name"],
+        &get_fields::<Test>(),
+    );
+    assert_eq!(get_fields::<Test>().len(), 3);
+
+    let canonical = |path| Test::canonical_path(path);
+    assert_eq!(canonical("user_name").as_deref(), Some("user_name"));
+    assert_eq!(canonical("login").as_deref(), Some("user_name"));
+    assert_eq!(canonical("address.town").as_deref(), Some("home.city"));
+    assert_eq!(canonical("home.town").as_deref(), Some("home.city"));
+    assert_eq!(canonical("address").as_deref(), Some("home"));
+    assert_eq!(canonical("kind.first").as_deref(), Some("kind.first_name"));
+    assert_eq!(canonical("home_address"), None);
+    assert_eq!(canonical("home.street"), None);
+    assert_eq!(canonical("user_name.nested"), None);
+}
+
+#[test]
+fn deprecation_and_versions_are_exposed() {
+    #[derive(Fields)]
+    struct Test {
+        id: String,
+        #[fields(deprecated = "use `id`")]
+        legacy_id: String,
+        #[deprecated(since = "1.2.0", note = "use `address`")]
+        location: String,
+        #[deprecated]
+        old: String,
+        #[fields(since = "1.4")]
+        address: Address,
+        #[fields(since = "1.10")]
+        tags: Vec<String>,
+    }
+
+    #[derive(Fields)]
+    struct Address {
+        city: String,
+        #[fields(since = "1.5", deprecated)]
+        zip: String,
+    }
+
+    assert_eq!(Test::deprecation("id"), None);
+    assert_eq!(Test::deprecation("legacy_id"), Some("use `id`"));
+    assert_eq!(Test::deprecation("location"), Some("use `address`"));
+    assert_eq!(Test::deprecation("old"), Some(""));
+    assert_eq!(Test::deprecation("address.zip"), Some(""));
+    assert_eq!(Test::field("address").unwrap().since(), Some("1.4"));
+    assert_eq!(Test::field("address.zip").unwrap().since(), Some("1.5"));
+
+    assert_eq!(
+        paths(Test::fields_at_version("1.3")),
+        ["id", "legacy_id", "location", "old"]
+    );
+    assert_eq!(
+        paths(Test::fields_at_version("1.4")),
+        ["address.city", "id", "legacy_id", "location", "old"]
+    );
+    assert_eq!(
+        paths(Test::fields_at_version("1.9.1")),
+        [
+            "address.city",
+            "address.zip",
+            "id",
+            "legacy_id",
+            "location",
+            "old"
+        ]
+    );
+    assert_eq!(paths(Test::fields_at_version("1.10")).len(), 7);
+}
+
+#[test]
+fn removed_fields_are_excluded_from_their_until_version() {
+    #[derive(Fields)]
+    struct Test {
+        id: String,
+        #[fields(until = "2.0")]
+        legacy_id: String,
+        #[fields(since = "1.2", until = "1.10")]
+        location: Location,
+    }
+
+    #[derive(Fields)]
+    struct Location {
+        #[fields(until = "1.5")]
+        lat: f64,
+        name: String,
+    }
+
+    assert_eq!(Test::field("legacy_id").unwrap().until(), Some("2.0"));
+    assert_eq!(Test::field("id").unwrap().until(), None);
+
+    assert_eq!(paths(Test::fields_at_version("1.1")), ["id", "legacy_id"]);
+    assert_eq!(
+        paths(Test::fields_at_version("1.4.9")),
+        ["id", "legacy_id", "location.lat", "location.name"]
+    );
+    assert_eq!(
+        paths(Test::fields_at_version("1.5")),
+        ["id", "legacy_id", "location.name"]
+    );
+    assert_eq!(paths(Test::fields_at_version("1.10")), ["id", "legacy_id"]);
+    assert_eq!(paths(Test::fields_at_version("2.0")), ["id"]);
+}
+
+#[test]
+fn doc_comments_are_descriptions() {
+    /// A user of the system.
+    ///
+    /// Stored in the `users` table.
+    #[derive(Fields)]
+    struct User {
+        /// Unique identifier.
+        id: String,
+        /// Where the user lives.
+        address: Address,
+        undocumented: String,
+    }
+
+    #[derive(Fields)]
+    struct Address {
+        /// City,
+        ///   without the postal code.
+        city: String,
+        #[doc = "Street name."]
+        street: String,
+    }
+
+    assert_eq!(
+        User::DOC,
+        Some("A user of the system.\n\nStored in the `users` table.")
+    );
+    assert_eq!(Address::DOC, None);
+    assert_eq!(User::description("id"), Some("Unique identifier."));
+    assert_eq!(User::description("address"), Some("Where the user lives."));
+    assert_eq!(
+        User::description("address.city"),
+        Some("City,\n  without the postal code.")
+    );
+    assert_eq!(User::description("address.street"), Some("Street name."));
+    assert_eq!(User::description("undocumented"), None);
+    assert_eq!(User::description("missing"), None);
+}
+
+#[test]
+fn sensitive_fields_are_propagated_and_redacted() {
+    #[derive(Fields)]
+    #[fields(redacted_debug)]
+    struct Request {
+        id: u32,
+        user: User,
+        #[fields(sensitive)]
+        token: Token,
+    }
+
+    #[derive(Fields)]
+    #[fields(redacted_debug)]
+    struct User {
+        name: String,
+        #[fields(sensitive, rename = "pass")]
+        password: String,
+    }
+
+    #[derive(Fields, Debug)]
+    struct Token {
+        value: String,
+    }
+
+    #[derive(Fields)]
+    #[fields(redacted_debug)]
+    enum Credentials {
+        Basic {
+            user: String,
+            #[fields(sensitive)]
+            password: String,
+        },
+        Bearer(#[fields(sensitive)] String),
+        Anonymous,
+    }
+
+    #[derive(Fields)]
+    #[fields(redacted_debug)]
+    struct Wrapper<T: Fields>(T);
+
+    let mut sensitive: Vec<_> = Request::sensitive_fields().unwrap().into_iter().collect();
+    sensitive.sort();
+    assert_eq!(sensitive, ["token.value", "user.pass"]);
+
+    let request = Request {
+        id: 1,
+        user: User {
+            name: "john".to_string(),
+            password: "secret".to_string(),
+        },
+        token: Token {
+            value: "secret".to_string(),
+        },
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"Request { id: 1, user: User { name: "john", password: *** }, token: *** }"#
+    );
+
+    let basic = Credentials::Basic {
+        user: "john".to_string(),
+        password: "secret".to_string(),
+    };
+    assert_eq!(
+        format!("{:?}", basic),
+        r#"Basic { user: "john", password: *** }"#
+    );
+    assert_eq!(
+        format!("{:?}", Credentials::Bearer("secret".to_string())),
+        "Bearer(***)"
+    );
+    assert_eq!(format!("{:?}", Credentials::Anonymous), "Anonymous");
+    assert_eq!(format!("{:?}", Wrapper(1)), "Wrapper(1)");
+}
+
+#[test]
+fn user_metadata_is_merged_along_paths() {
+    use fields::MetaValue;
+
+    #[derive(Fields)]
+    #[fields(meta(table = "users", index = false))]
+    struct User {
+        #[fields(meta(index, order = -1))]
+        id: u64,
+        #[fields(meta(widget = "textarea", max_length = 500, weight = 0.5))]
+        bio: String,
+        #[fields(meta(i18n = "user.address"))]
+        address: Address,
+    }
+
+    #[derive(Fields)]
+    #[fields(meta(table = "addresses"))]
+    struct Address {
+        #[fields(meta(i18n = "address.city"))]
+        city: String,
+        street: String,
+    }
+
+    let meta = |path| User::meta(path).unwrap().into_iter().collect::<Vec<_>>();
+
+    assert_eq!(
+        User::META,
+        [
+            ("table", MetaValue::Str("users")),
+            ("index", MetaValue::Bool(false))
+        ]
+    );
+    assert_eq!(
+        meta("id"),
+        [
+            ("index", MetaValue::Bool(true)),
+            ("order", MetaValue::Int(-1)),
+            ("table", MetaValue::Str("users"))
+        ]
+    );
+    assert_eq!(
+        meta("bio"),
+        [
+            ("index", MetaValue::Bool(false)),
+            ("max_length", MetaValue::Int(500)),
+            ("table", MetaValue::Str("users")),
+            ("weight", MetaValue::Float(0.5)),
+            ("widget", MetaValue::Str("textarea"))
+        ]
+    );
+    assert_eq!(
+        meta("address.city"),
+        [
+            ("i18n", MetaValue::Str("address.city")),
+            ("index", MetaValue::Bool(false)),
+            ("table", MetaValue::Str("addresses"))
+        ]
+    );
+    assert_eq!(
+        User::meta("address.street").unwrap()["i18n"].as_str(),
+        Some("user.address")
+    );
+    assert!(User::meta("missing").is_none());
+}
+
+#[test]
+fn metadata_integers_cover_the_i64_range() {
+    use fields::MetaValue;
+
+    #[derive(Fields)]
+    #[fields(meta(min = -9223372036854775808, max = 9223372036854775807))]
+    struct Bounds {
+        value: i64,
+    }
+
+    assert_eq!(
+        Bounds::META,
+        [
+            ("min", MetaValue::Int(i64::MIN)),
+            ("max", MetaValue::Int(i64::MAX))
+        ]
+    );
+}
+
+#[test]
+fn naming_profiles_select_field_names() {
+    #[derive(Fields)]
+    #[fields(rename_all(json = "camelCase", env = "SCREAMING_SNAKE_CASE"))]
+    struct Account {
+        #[fields(rename(json = "accountId", sql = "acct_id"))]
+        account_id: u64,
+        #[fields(rename = "name")]
+        display_name: String,
+        billing_address: Address,
+    }
+
+    #[derive(Fields)]
+    #[fields(rename_all(json = "camelCase"))]
+    struct Address {
+        #[fields(rename(sql = "postal"))]
+        zip_code: String,
+    }
+
+    let fields_for = |profile| {
+        let mut fields: Vec<_> = Account::fields_for(profile).unwrap().into_iter().collect();
+        fields.sort();
+        fields
+    };
+
+    assert_eq!(
+        fields_for("json"),
+        ["accountId", "billingAddress.zipCode", "name"]
+    );
+    assert_eq!(
+        fields_for("sql"),
+        ["acct_id", "billing_address.postal", "name"]
+    );
+    assert_eq!(
+        fields_for("env"),
+        ["ACCOUNT_ID", "BILLING_ADDRESS.zip_code", "name"]
+    );
+    assert_eq!(
+        fields_for("unknown"),
+        ["account_id", "billing_address.zip_code", "name"]
+    );
+    assert_eq!(
+        Account::field("account_id").unwrap().name_for("sql"),
+        Some("acct_id")
+    );
+}
+
+#[test]
+fn case_is_applied_at_runtime() {
+    use fields::Case;
+
+    #[derive(Fields)]
+    #[fields(rename_all = "camelCase")]
+    struct User {
+        user_id: u64,
+        #[fields(rename = "mail")]
+        email_address: String,
+        home_address: Address,
+        r#type: String,
+    }
+
+    #[derive(Fields)]
+    struct Address {
+        zip_code: String,
+    }
+
+    let fields_with_case = |case| {
+        let mut fields: Vec<_> = User::fields_with_case(case).unwrap().into_iter().collect();
+        fields.sort();
+        fields
+    };
+
+    assert_eq!(
+        fields_with_case(Case::Kebab),
+        ["home-address.zip-code", "mail", "type", "user-id"]
+    );
+    assert_eq!(
+        fields_with_case(Case::ScreamingSnake),
+        ["HOME_ADDRESS.ZIP_CODE", "TYPE", "USER_ID", "mail"]
+    );
+    assert_eq!(
+        fields_with_case(Case::Pascal),
+        ["HomeAddress.ZipCode", "Type", "UserId", "mail"]
+    );
+    assert_eq!(User::field("userId").unwrap().ident(), Some("user_id"));
+    assert!(User::field("mail").unwrap().is_renamed());
+}
+
+macro_rules! serde_parity {
+    ($($rule:literal => $case:ident),* $(,)?) => {$({
+        #[derive(Default, Fields, serde::Serialize)]
+        #[fields(rename_all = $rule)]
+        #[serde(rename_all = $rule)]
+        struct Test {
+            user_id: u64,
+            http_2_url: String,
+            sha256_hash: String,
+            type_: String,
+            _private: bool,
+            r#match: bool,
+            a: bool,
+        }
+
+        let serialized = serde_json::to_value(Test::default()).unwrap();
+        let serde_names: FieldSet = serialized.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(Test::fields().as_ref(), Some(&serde_names), "{}", $rule);
+        assert_eq!(
+            Test::fields_with_case(fields::Case::$case).as_ref(),
+            Some(&serde_names),
+            "{}",
+            $rule
+        );
+    })*};
+}
+
+#[test]
+fn rename_all_matches_serde() {
+    serde_parity! {
+        "PascalCase" => Pascal,
+        "camelCase" => Camel,
+        "snake_case" => Snake,
+        "SCREAMING_SNAKE_CASE" => ScreamingSnake,
+        "kebab-case" => Kebab,
+        "SCREAMING-KEBAB-CASE" => ScreamingKebab,
+    }
+}
+
+#[test]
+fn rename_all_splits_acronyms_digits_and_unicode() {
+    use fields::Case;
+
+    #[derive(Fields)]
+    #[fields(rename_all = "snake_case")]
+    #[allow(non_snake_case)]
+    struct Snake {
+        parseHTTPResponse: u8,
+        userID: u8,
+        ipv4Address: u8,
+        größe_maß: u8,
+    }
+
+    #[derive(Fields)]
+    #[fields(rename_all = "Train-Case")]
+    struct Train {
+        http_2_url: u8,
+        größe_maß: u8,
+    }
+
+    #[derive(Fields)]
+    #[fields(rename_all = "UPPERCASE")]
+    struct Upper {
+        user_id: u8,
+        größe_maß: u8,
+        type_: u8,
+    }
+
+    #[derive(Fields)]
+    #[fields(rename_all = "camelCase")]
+    struct Camel {
+        élan_vital: u8,
+    }
+
+    assert_eq!(
+        get_fields::<Snake>(),
+        [
+            "größe_maß",
+            "ipv4_address",
+            "parse_http_response",
+            "user_id"
+        ]
+    );
+    assert_eq!(get_fields::<Train>(), ["Größe-Maß", "Http-2-Url"]);
+    assert_eq!(get_fields::<Upper>(), ["GRÖSSEMASS", "TYPE", "USERID"]);
+    assert_eq!(get_fields::<Camel>(), ["élanVital"]);
+
+    assert_eq!(Case::Dot.apply("parseHTTPResponse"), "parse.http.response");
+    assert_eq!(Case::Title.apply("first_name"), "First Name");
+    assert_eq!(Case::Lower.apply("parseHTTP_response"), "parsehttpresponse");
+    assert_eq!(Case::ScreamingKebab.apply("größe_maß"), "GRÖSSE-MASS");
+    assert_eq!(Case::Camel.apply("HTTPServer"), "httpServer");
+    assert_eq!(&Snake::fields_with_case(Case::Snake), Snake::fields());
+    assert_eq!(&Train::fields_with_case(Case::Train), Train::fields());
+    assert_eq!(&Upper::fields_with_case(Case::Upper), Upper::fields());
+    assert_eq!(&Camel::fields_with_case(Case::Camel), Camel::fields());
+}
+
+#[test]
+fn rename_all_nested_recases_nested_types() {
+    #[derive(Fields)]
+    #[fields(rename_all_nested = "camelCase")]
+    struct Order {
+        order_id: u64,
+        billing_address: Address,
+        #[fields(rename_all_nested = "SCREAMING_SNAKE_CASE")]
+        shipping_address: Address,
+    }
+
+    #[derive(Fields)]
+    struct Customer {
+        #[fields(rename_all_nested = "kebab-case")]
+        home_address: Address,
+        last_order: Order,
+    }
+
+    #[derive(Fields)]
+    #[fields(rename_all = "PascalCase")]
+    struct Address {
+        street_name: String,
+        #[fields(rename = "zip")]
+        postal_code: String,
+        geo_point: GeoPoint,
+    }
+
+    #[derive(Fields)]
+    struct GeoPoint {
+        lat_deg: f64,
+    }
+
+    assert_eq!(
+        get_fields::<Order>(),
+        [
+            "billingAddress.geoPoint.latDeg",
+            "billingAddress.streetName",
+            "billingAddress.zip",
+            "orderId",
+            "shippingAddress.GEO_POINT.LAT_DEG",
+            "shippingAddress.STREET_NAME",
+            "shippingAddress.zip",
+        ]
+    );
+    assert_eq!(
+        get_fields::<Customer>(),
+        [
+            "home_address.geo-point.lat-deg",
+            "home_address.street-name",
+            "home_address.zip",
+            "last_order.billingAddress.geoPoint.latDeg",
+            "last_order.billingAddress.streetName",
+            "last_order.billingAddress.zip",
+            "last_order.orderId",
+            "last_order.shippingAddress.GEO_POINT.LAT_DEG",
+            "last_order.shippingAddress.STREET_NAME",
+            "last_order.shippingAddress.zip",
+        ]
+    );
+    assert_eq!(
+        get_fields::<Address>(),
+        ["GeoPoint.lat_deg", "StreetName", "zip"]
+    );
+
+    let mut const_names = <Customer as FieldNames>::FIELD_NAMES.to_vec();
+    const_names.sort();
+    assert_eq!(const_names, get_fields::<Customer>());
+    assert_eq!(
+        fields::path!(Customer, last_order.shippingAddress.GEO_POINT.LAT_DEG),
+        "last_order.shippingAddress.GEO_POINT.LAT_DEG"
+    );
+    assert_eq!(
+        Customer::canonical_path("home_address.geo-point.lat-deg").as_deref(),
+        Some("home_address.geo-point.lat-deg")
+    );
+    assert!(Customer::field("home_address.GeoPoint").is_none());
+}
+
+macro_rules! const_case_parity {
+    ($($rule:literal),* $(,)?) => {$({
+        #[derive(Fields)]
+        struct Outer {
+            #[fields(rename_all_nested = $rule)]
+            inner: Inner,
+        }
+
+        #[derive(Fields)]
+        #[allow(non_snake_case)]
+        struct Inner {
+            user_id: u8,
+            http_2_url: u8,
+            parseHTTPResponse: u8,
+            type_: u8,
+            _private: u8,
+            größe: u8,
+        }
+
+        let names: FieldSet = <Outer as FieldNames>::FIELD_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(Some(names), *Outer::fields(), "{}", $rule);
+    })*};
+}
+
+#[test]
+fn const_field_names_recase_like_runtime() {
+    const_case_parity! {
+        "lowercase",
+        "UPPERCASE",
+        "PascalCase",
+        "camelCase",
+        "snake_case",
+        "SCREAMING_SNAKE_CASE",
+        "kebab-case",
+        "SCREAMING-KEBAB-CASE",
+        "Train-Case",
+    }
+}
+
+#[test]
+fn container_defaults_for_nesting_and_skipping() {
+    #[derive(Fields)]
+    #[fields(nested = false)]
+    struct Flat {
+        a: Nested,
+        #[fields(nested = true)]
+        b: Nested,
+    }
+
+    #[derive(Fields)]
+    #[fields(skip_all)]
+    enum Allowlist {
+        A {
+            #[fields(include)]
+            id: u64,
+            secret: String,
+        },
+        B(#[fields(include)] Nested, Nested),
+    }
+
+    #[derive(Fields)]
+    struct Nested {
+        c: i32,
+    }
+
+    assert_eq!(get_fields::<Flat>(), ["a", "b.c"]);
+    assert_eq!(get_fields::<Allowlist>(), ["c", "id"]);
+}
+
+#[test]
+fn transparent_structs_use_fields_of_their_field() {
+    #[derive(Fields)]
+    #[fields(transparent)]
+    struct Wrapper {
+        inner: Inner,
+        #[fields(skip)]
+        _marker: (),
+    }
+
+    #[derive(Fields)]
+    #[fields(transparent)]
+    struct Opaque(#[fields(nested = false)] Inner);
+
+    #[derive(Fields)]
+    #[fields(transparent)]
+    struct Meters(f64);
+
+    #[derive(Fields)]
+    struct Inner {
+        a: i32,
+        b: Nested,
+    }
+
+    #[derive(Fields)]
+    struct Nested {
+        c: i32,
+    }
+
+    #[derive(Fields)]
+    struct Test {
+        wrapper: Wrapper,
+        opaque: Opaque,
+        distance: Meters,
+    }
+
+    assert_eq!(Wrapper::fields(), Inner::fields());
+    assert!(Opaque::fields().is_none());
+    assert!(Meters::fields().is_none());
+    assert_fields(
+        &["wrapper.a", "wrapper.b.c", "opaque", "distance"],
+        &get_fields::<Test>(),
+    );
+    assert_eq!(get_fields::<Test>().len(), 4);
+    assert_eq!(fields::path!(Test, wrapper.b.c), "wrapper.b.c");
+}
+
+mod remote {
+    pub struct Message {
+        pub id: u64,
+        pub payload: Option<Payload>,
+    }
+
+    pub struct Payload {
+        pub body: String,
+        size: usize,
+    }
+
+    #[non_exhaustive]
+    pub enum Status {
+        Active { since: u64 },
+        Closed(u8),
+        Unknown,
+    }
+}
+
+#[test]
+fn remote_types_are_described_by_mirrors() {
+    #[derive(Fields)]
+    #[fields(remote = "remote::Message")]
+    #[allow(dead_code)]
+    struct MessageDef {
+        id: u64,
+        #[fields(with = "PayloadDef")]
+        payload: Option<remote::Payload>,
+    }
+
+    #[derive(Fields)]
+    #[fields(remote = "remote::Payload")]
+    #[allow(dead_code)]
+    struct PayloadDef {
+        body: String,
+        #[fields(skip)]
+        size: usize,
+    }
+
+    #[derive(Fields)]
+    #[fields(remote = "remote::Status")]
+    #[allow(dead_code)]
+    enum StatusDef {
+        Active { since: u64 },
+        Closed(u8),
+        Unknown,
+    }
+
+    #[derive(Fields)]
+    struct Envelope {
+        #[fields(with = "MessageDef")]
+        message: remote::Message,
+        #[fields(with = "StatusDef")]
+        status: remote::Status,
+    }
+
+    assert_fields(
+        &["message.id", "message.payload.body", "status.since"],
+        &get_fields::<Envelope>(),
+    );
+    assert_eq!(get_fields::<Envelope>().len(), 3);
+    assert_eq!(
+        fields::path!(Envelope, message.payload.body),
+        "message.payload.body"
+    );
+}
+
+#[test]
+fn with_accepts_primitive_and_lowercase_types() {
+    #[allow(non_camel_case_types)]
+    type payload = Payload;
+
+    #[derive(Fields)]
+    struct Payload {
+        body: String,
+    }
+
+    #[derive(Fields)]
+    struct Reading {
+        #[fields(with = "f64")]
+        value: Box<f64>,
+        #[fields(with = "payload")]
+        payload: Vec<u8>,
+    }
+
+    assert_fields(&["value", "payload.body"], &get_fields::<Reading>());
+    assert_eq!(get_fields::<Reading>().len(), 2);
+    assert_eq!(fields::path!(Reading, payload.body), "payload.body");
+}
+
+trait Plugin {}
+
+fn plugin_fields() -> Option<FieldSet> {
+    Some(
+        ["name", "config.enabled"]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+    )
+}
+
+#[test]
+fn provider_functions_supply_nested_fields() {
+    #[derive(Fields)]
+    struct Host {
+        id: u64,
+        #[fields(with_fn = "plugin_fields", sensitive)]
+        plugin: Box<dyn Plugin>,
+        #[fields(with_fn = "Nested::fields_cloned")]
+        nested: Vec<u8>,
+    }
+
+    struct Nested;
+
+    impl Nested {
+        fn fields_cloned() -> Option<FieldSet> {
+            None
+        }
+    }
+
+    assert_fields(
+        &["id", "plugin.name", "plugin.config.enabled", "nested"],
+        &get_fields::<Host>(),
+    );
+    assert_eq!(get_fields::<Host>().len(), 4);
+    assert_eq!(
+        Host::canonical_path("plugin.config.enabled").as_deref(),
+        Some("plugin.config.enabled")
+    );
+    assert_eq!(
+        Host::canonical_path("plugin.config").as_deref(),
+        Some("plugin.config")
+    );
+    assert!(Host::canonical_path("plugin.missing").is_none());
+    assert!(Host::canonical_path("plugin.conf").is_none());
+    assert!(Host::field("plugin").is_some());
+    assert!(Host::field("plugin.name").is_none());
+    assert_eq!(Host::sensitive_fields().map(|fields| fields.len()), Some(2));
+    assert_eq!(fields::path!(Host, plugin), "plugin");
+}
+
+#[test]
+fn dyn_fields_and_registry_look_up_fields_at_runtime() {
+    use std::any::Any;
+
+    use fields::{DynFields, Registry, TypeFields};
+
+    #[derive(Fields)]
+    struct User {
+        name: String,
+    }
+
+    #[derive(Fields)]
+    struct Wrapper<T: Fields> {
+        inner: T,
+    }
+
+    let entities: Vec<Box<dyn DynFields>> = vec![
+        Box::new(User {
+            name: "john".to_string(),
+        }),
+        Box::new(Wrapper {
+            inner: User {
+                name: "jane".to_string(),
+            },
+        }),
+    ];
+    assert!(entities[0].dyn_fields().as_ref().unwrap().contains("name"));
+    assert!(entities[1].dyn_field_tree().is_some());
+    assert!(entities[1].dyn_type_name().contains("Wrapper<"));
+
+    let mut registry = Registry::new();
+    assert!(registry.is_empty());
+    registry.register::<User>().register::<Wrapper<User>>();
+    assert_eq!(
+        registry.iter().map(TypeFields::name).collect::<Vec<_>>(),
+        ["User", "Wrapper"]
+    );
+    let wrapper = *registry.get("Wrapper").unwrap();
+    assert!(wrapper.fields().as_ref().unwrap().contains("inner.name"));
+    assert!(wrapper.type_name().ends_with("::User>"));
+
+    let value: &dyn Any = &User {
+        name: "john".to_string(),
+    };
+    assert_eq!(registry.get_for(value).unwrap().name(), "User");
+    assert!(registry.get_for(&1u8).is_none());
+
+    let previous = registry.insert(TypeFields::named::<User>("Wrapper"));
+    assert!(previous.unwrap().type_name().contains("Wrapper"));
+    assert_eq!(registry.len(), 2);
+    assert!(registry.get_by_type_id(wrapper.type_id()).is_none());
+    let entity: &dyn DynFields = registry.get("Wrapper").unwrap();
+    assert!(entity.dyn_fields().as_ref().unwrap().contains("name"));
+
+    registry.insert(TypeFields::named::<Wrapper<User>>("User"));
+    assert_eq!(registry.get_for(value).unwrap().name(), "Wrapper");
+    assert_eq!(
+        registry.get_by_type_id(wrapper.type_id()).unwrap().name(),
+        "User"
+    );
+}
+
+mod registered {
+    use fields::Fields;
+
+    #[derive(Fields)]
+    #[fields(register)]
+    pub struct Invoice {
+        pub number: String,
+        pub customer: Customer,
+    }
+
+    #[derive(Fields)]
+    #[fields(register, rename_all = "camelCase")]
+    pub enum Customer {
+        Person { full_name: String },
+    }
+}
+
+#[test]
+fn registered_types_are_listed_at_runtime() {
+    use fields::{registered_types, Registry};
+
+    let registered: Vec<_> = registered_types()
+        .filter(|registered| registered.module_path() == Some("test::registered"))
+        .collect();
+    assert_eq!(registered.len(), 2);
+
+    let registry = Registry::registered();
+    let invoice = registry.get("Invoice").unwrap();
+    assert_eq!(invoice.module_path(), Some("test::registered"));
+    assert_eq!(
+        invoice.type_id(),
+        std::any::TypeId::of::<registered::Invoice>()
+    );
+    let fields = invoice.fields().as_ref().unwrap();
+    assert!(fields.contains("number") && fields.contains("customer.fullName"));
+    assert!(registry.get("Customer").unwrap().field_tree().is_some());
+    assert!(registry.get("User").is_none());
+}
+
+#[test]
+fn field_sets_support_set_algebra_and_subtrees() {
+    #[derive(Fields)]
+    struct User {
+        id: u64,
+        address: Address,
+        addresses: Vec<String>,
+    }
+
+    #[derive(Fields)]
+    struct Address {
+        city: String,
+        geo: Geo,
+    }
+
+    #[derive(Fields)]
+    struct Geo {
+        lat: f64,
+        lng: f64,
+    }
+
+    let all = User::field_set().unwrap();
+    assert_eq!(
+        all.iter().collect::<Vec<_>>(),
+        [
+            "address.city",
+            "address.geo.lat",
+            "address.geo.lng",
+            "addresses",
+            "id"
+        ]
+    );
+    let set: FieldSet = ["id", "address.geo.lat", "unknown"].into_iter().collect();
+    assert_eq!((&set | &all).len(), 6);
+    assert_eq!((&set & &all).to_string(), "address.geo.lat,id");
+    assert_eq!((&set - &all).to_string(), "unknown");
+    assert_eq!((&set ^ &all).len(), 4);
+    assert!((&set & &all).is_subset(&all) && all.is_superset(&(&set & &all)));
+
+    assert_eq!(all.subtree("address").len(), 3);
+    assert_eq!(
+        all.subtree("address.geo.lat").to_string(),
+        "address.geo.lat"
+    );
+    assert!(all.subtree("addr").is_empty());
+    assert_eq!(all.subtree(""), all);
+    assert!(all.contains_subtree("address.geo") && !all.contains_subtree("geo"));
+    assert_eq!(
+        all.strip_prefix("address").to_string(),
+        "city,geo.lat,geo.lng"
+    );
+    assert_eq!(all.strip_prefix("address").with_prefix("home").len(), 3);
+    assert_eq!(
+        all.collapse("address.geo").to_string(),
+        "address.city,address.geo,addresses,id"
+    );
+    assert_eq!(all.collapse("missing"), all);
+
+    let mut nested: FieldSet = all.clone();
+    nested.insert("address.geo");
+    assert!(nested.remove("address.city"));
+    assert_eq!(nested.roots().to_string(), "address.geo,addresses,id");
+
+    let hash_set: HashSet<String> = all.clone().into();
+    assert_eq!(FieldSet::from(hash_set), all);
+    let btree_set: BTreeSet<String> = all.clone().into();
+    assert_eq!(FieldSet::from(btree_set), all);
+    assert_eq!(&all, User::fields().as_ref().unwrap());
+    let mut extended = FieldSet::new();
+    extended.extend(["b", "a"]);
+    assert_eq!(extended.into_iter().collect::<Vec<_>>(), ["a", "b"]);
+}
+
+#[test]
+fn field_masks_are_parsed_validated_and_canonicalized() {
+    use fields::{FieldMask, FieldMaskError};
+
+    #[derive(Fields)]
+    struct User {
+        id: u64,
+        display_name: String,
+        #[fields(alias = "home")]
+        address: Address,
+    }
+
+    #[derive(Fields)]
+    struct Address {
+        city: String,
+        street_name: String,
+    }
+
+    let mask = FieldMask::parse(" address.city ,, id,address,home.city ").unwrap();
+    assert_eq!(mask.paths(), ["address.city", "id", "address", "home.city"]);
+    assert_eq!(mask.normalize().to_string(), "address,home.city,id");
+    assert_eq!(
+        mask.canonicalize::<User>().unwrap().to_string(),
+        "address.city,address.street_name,id"
+    );
+    assert!(mask.validate::<User>().is_ok());
+    assert!(mask.contains("address.street_name") && !mask.contains("display_name"));
+    assert!(FieldMask::parse("").unwrap().is_empty());
+
+    assert_eq!(
+        FieldMask::parse("id,address..city"),
+        Err(FieldMaskError::InvalidPath("address..city".to_string()))
+    );
+    assert!("display name".parse::<FieldMask>().is_err());
+    let unknown: FieldMask = "id,id.value,name".parse().unwrap();
+    assert_eq!(
+        unknown.validate::<User>(),
+        Err(FieldMaskError::UnknownPath("id.value".to_string()))
+    );
+    assert_eq!(
+        unknown.canonicalize::<User>().unwrap_err().to_string(),
+        "unknown field mask path `id.value`"
+    );
+
+    let mask: FieldMask = "display_name,address.street_name".parse().unwrap();
+    assert_eq!(
+        mask.to_json_string().unwrap(),
+        "displayName,address.streetName"
+    );
+    assert_eq!(
+        FieldMask::from_json_string("displayName,address.streetName").unwrap(),
+        mask
+    );
+    assert!(FieldMask::from_json_string("display_name").is_err());
+    assert!(FieldMask::parse("displayName")
+        .unwrap()
+        .to_json_string()
+        .is_err());
+    assert!(FieldMask::parse("name_1")
+        .unwrap()
+        .to_json_string()
+        .is_err());
+
+    let a: FieldMask = "address,id".parse().unwrap();
+    let b: FieldMask = "address.city,display_name,id.value".parse().unwrap();
+    assert_eq!(a.union(&b).to_string(), "address,display_name,id");
+    assert_eq!(a.intersection(&b).to_string(), "address.city,id.value");
+    assert_eq!(a.to_field_set().len(), 2);
+    assert_eq!(FieldMask::from(User::field_set().unwrap()).len(), 4);
+}
+
+#[test]
+fn apply_mask_copies_selected_fields() {
+    use fields::{ApplyMask, FieldMask};
+
+    #[derive(Fields, Debug, Clone, PartialEq, Default)]
+    #[fields(apply_mask, rename_all_nested = "camelCase")]
+    struct User {
+        display_name: String,
+        #[fields(rename = "home")]
+        address: Address,
+        work_address: Option<Address>,
+        tags: Vec<String>,
+        id: Id,
+        #[fields(skip)]
+        version: u32,
+    }
+
+    #[derive(Fields, Debug, Clone, PartialEq, Default)]
+    #[fields(apply_mask)]
+    struct Address {
+        city: String,
+        street_name: String,
+    }
+
+    #[derive(Fields, Debug, Clone, PartialEq, Default)]
+    #[fields(apply_mask, transparent)]
+    struct Id(u64);
+
+    let address = |city: &str, street_name: &str| Address {
+        city: city.to_string(),
+        street_name: street_name.to_string(),
+    };
+    let target = User {
+        display_name: "john".to_string(),
+        address: address("Berlin", "Main"),
+        work_address: Some(address("Paris", "Rue")),
+        tags: vec!["a".to_string()],
+        id: Id(1),
+        version: 1,
+    };
+    let source = User {
+        display_name: "jane".to_string(),
+        address: address("Oslo", "High"),
+        work_address: Some(address("Rome", "Via")),
+        tags: vec!["b".to_string(), "c".to_string()],
+        id: Id(2),
+        version: 2,
+    };
+
+    let patch = |mask: &str| {
+        let mut patched = target.clone();
+        patched.apply_mask(source.clone(), &mask.parse::<FieldMask>().unwrap());
+        patched
+    };
+
+    assert_eq!(patch(""), target);
+    assert_eq!(
+        patch("home.streetName,workAddress.city,id").address,
+        address("Berlin", "High")
+    );
+    assert_eq!(
+        patch("home.streetName,workAddress.city,id").work_address,
+        Some(address("Rome", "Rue"))
+    );
+    assert_eq!(patch("id").id, Id(2));
+    assert_eq!(patch("home,tags").address, source.address);
+    assert_eq!(patch("home,tags").tags, source.tags);
+    assert_eq!(patch("displayName").display_name, "jane");
+    assert_eq!(patch("display_name,address").display_name, "john");
+
+    let everything = patch(&User::field_set().unwrap().to_string());
+    assert_eq!(
+        everything,
+        User {
+            version: 1,
+            ..source.clone()
+        }
+    );
+
+    let mut unset = User {
+        work_address: None,
+        ..target.clone()
+    };
+    unset.apply_mask(source.clone(), &"workAddress.city".parse().unwrap());
+    assert_eq!(unset.work_address, source.work_address);
+
+    let cleared = User {
+        work_address: None,
+        ..source.clone()
+    };
+    let mut kept = target.clone();
+    kept.apply_mask(cleared.clone(), &"workAddress.city".parse().unwrap());
+    assert_eq!(kept.work_address, target.work_address);
+    let mut replaced = target.clone();
+    replaced.apply_mask(cleared, &"workAddress".parse().unwrap());
+    assert_eq!(replaced.work_address, None);
+}
+
+#[test]
+fn apply_mask_matches_aliases() {
+    use fields::{ApplyMask, FieldMask};
+
+    #[derive(Fields, Debug, Clone, PartialEq, Default)]
+    #[fields(apply_mask)]
+    struct User {
+        #[fields(alias = "home")]
+        address: Address,
+        #[fields(alias = "login")]
+        name: String,
+    }
+
+    #[derive(Fields, Debug, Clone, PartialEq, Default)]
+    #[fields(apply_mask)]
+    struct Address {
+        #[fields(alias = "town")]
+        city: String,
+        street: String,
+    }
+
+    let user = |name: &str, city: &str, street: &str| User {
+        address: Address {
+            city: city.to_string(),
+            street: street.to_string(),
+        },
+        name: name.to_string(),
+    };
+    let patch = |mask: &str| {
+        let mut patched = user("john", "Berlin", "Main");
+        patched.apply_mask(
+            user("jane", "Oslo", "High"),
+            &mask.parse::<FieldMask>().unwrap(),
+        );
+        patched
+    };
+
+    assert_eq!(patch("login"), user("jane", "Berlin", "Main"));
+    assert_eq!(patch("home.town"), user("john", "Oslo", "Main"));
+    assert_eq!(patch("home"), user("john", "Oslo", "High"));
+    assert_eq!(
+        patch("address.town,home.street"),
+        user("john", "Oslo", "High")
+    );
+}
+
+#[test]
+fn apply_mask_replaces_enums_as_a_whole() {
+    use fields::{ApplyMask, FieldMask};
+
+    #[derive(Fields, Debug, Clone, PartialEq)]
+    #[fields(apply_mask)]
+    struct Account {
+        name: String,
+        kind: Kind,
+    }
+
+    #[derive(Fields, Debug, Clone, PartialEq)]
+    #[fields(apply_mask)]
+    enum Kind {
+        Person { first_name: String },
+        Company { tax_id: String },
+    }
+
+    let patch = |mask: &str| {
+        let mut account = Account {
+            name: "john".to_string(),
+            kind: Kind::Person {
+                first_name: "John".to_string(),
+            },
+        };
+        account.apply_mask(
+            Account {
+                name: "acme".to_string(),
+                kind: Kind::Company {
+                    tax_id: "42".to_string(),
+                },
+            },
+            &mask.parse::<FieldMask>().unwrap(),
+        );
+        account
+    };
+
+    assert_eq!(
+        patch("name").kind,
+        Kind::Person {
+            first_name: "John".to_string()
+        }
+    );
+    let company = Kind::Company {
+        tax_id: "42".to_string(),
+    };
+    assert_eq!(patch("kind").kind, company);
+    assert_eq!(patch("kind.tax_id").kind, company);
+    assert_eq!(patch("kind.tax_id").name, "john");
+}
@@ -0,0 +1,26 @@
+use fields::Fields;
+
+#[derive(Fields)]
+struct User {
+    #[fields(alias = "login")]
+    name: String,
+    login: String,
+}
+
+#[derive(Fields)]
+enum Event {
+    Created {
+        #[fields(alias = "at")]
+        created_at: u64,
+    },
+    Deleted {
+        #[fields(alias = "at")]
+        deleted_at: u64,
+    },
+    Renamed {
+        #[fields(alias = "at")]
+        created_at: u64,
+    },
+}
+
+fn main() {}
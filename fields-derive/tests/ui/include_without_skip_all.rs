@@ -0,0 +1,10 @@
+use fields::Fields;
+
+#[derive(Fields)]
+struct User {
+    id: u64,
+    #[fields(include)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use fields::Fields;
+
+#[derive(Fields)]
+struct Limits {
+    #[fields(meta(min = -9223372036854775809))]
+    low: i64,
+    #[fields(meta(max = 9223372036854775808))]
+    high: i64,
+}
+
+fn main() {}
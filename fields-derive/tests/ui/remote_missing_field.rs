@@ -0,0 +1,17 @@
+use fields::Fields;
+
+mod other {
+    pub struct Message {
+        pub id: u64,
+    }
+}
+
+#[derive(Fields)]
+#[fields(remote = "other::Message")]
+#[allow(dead_code)]
+struct MessageDef {
+    id: u64,
+    body: String,
+}
+
+fn main() {}
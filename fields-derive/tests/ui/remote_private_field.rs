@@ -0,0 +1,18 @@
+use fields::Fields;
+
+mod other {
+    pub struct Message {
+        pub id: u64,
+        secret: String,
+    }
+}
+
+#[derive(Fields)]
+#[fields(remote = "other::Message")]
+#[allow(dead_code)]
+struct MessageDef {
+    id: u64,
+    secret: String,
+}
+
+fn main() {}
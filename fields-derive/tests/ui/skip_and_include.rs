@@ -0,0 +1,12 @@
+use fields::Fields;
+
+#[derive(Fields)]
+#[fields(skip_all)]
+struct User {
+    #[fields(include)]
+    id: u64,
+    #[fields(skip, include)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use fields::Fields;
+
+#[derive(Fields)]
+#[fields(renam_all = "camelCase")]
+struct SomeStruct {
+    #[fields(skpi)]
+    skipped: bool,
+    #[fields(unknown)]
+    other: i32,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use fields::{FieldNameSet, Fields};
+
+fn payload_fields() -> Option<FieldNameSet> {
+    None
+}
+
+#[derive(Fields)]
+struct Message {
+    #[fields(with = "String", with_fn = "payload_fields")]
+    payload: Vec<u8>,
+}
+
+fn main() {}
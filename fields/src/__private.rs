@@ -0,0 +1,673 @@
+//! Helpers used by the code generated by `fields_derive`. Not public API.
+use alloc::boxed::Box;
+
+use crate::field::{cased_name, child_case, collect_paths, path_names};
+use crate::field_set::relative_path;
+use crate::{Case, Field, FieldMask, FieldNameSet, FieldSet};
+
+/// Lazily initialized value used for caching field names in a `static`.
+pub struct Lazy<T> {
+    #[cfg(any(feature = "std", feature = "critical-section"))]
+    cell: once_cell::sync::OnceCell<T>,
+    #[cfg(not(any(feature = "std", feature = "critical-section")))]
+    cell: once_cell::race::OnceBox<T>,
+}
+
+impl<T> Lazy<T> {
+    #[cfg(any(feature = "std", feature = "critical-section"))]
+    pub const fn new() -> Self {
+        Self {
+            cell: once_cell::sync::OnceCell::new(),
+        }
+    }
+
+    #[cfg(not(any(feature = "std", feature = "critical-section")))]
+    pub const fn new() -> Self {
+        Self {
+            cell: once_cell::race::OnceBox::new(),
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        self.cell.get()
+    }
+
+    #[cfg(any(feature = "std", feature = "critical-section"))]
+    pub fn get_or_init(&self, init: impl FnOnce() -> T) -> &T {
+        self.cell.get_or_init(init)
+    }
+
+    #[cfg(not(any(feature = "std", feature = "critical-section")))]
+    pub fn get_or_init(&self, init: impl FnOnce() -> T) -> &T {
+        self.cell.get_or_init(|| Box::new(init()))
+    }
+}
+
+impl<T> Default for Lazy<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Field names of the instantiations of a generic type, cached in a `static`
+/// shared by all of them. Instantiations are told apart by the address of
+/// their field descriptions, so a description found at several addresses is
+/// collected once per address.
+pub struct GenericFieldNames {
+    head: Lazy<Box<Instance>>,
+}
+
+struct Instance {
+    fields: Option<&'static [Field]>,
+    names: Option<FieldNameSet>,
+    next: Lazy<Box<Instance>>,
+}
+
+impl GenericFieldNames {
+    pub const fn new() -> Self {
+        Self { head: Lazy::new() }
+    }
+
+    /// Field names collected from `fields`, appending them to the cached
+    /// instantiations the first time they are asked for. They are collected
+    /// before a cell is initialized, as providers may ask for the names of
+    /// other instantiations of the same type.
+    pub fn get(&'static self, fields: Option<&'static [Field]>) -> &'static Option<FieldNameSet> {
+        let mut next = &self.head;
+        loop {
+            let instance = match next.get() {
+                Some(instance) => instance,
+                None => {
+                    let names = field_names(fields);
+                    next.get_or_init(|| {
+                        Box::new(Instance {
+                            fields,
+                            names,
+                            next: Lazy::new(),
+                        })
+                    })
+                }
+            };
+            let same = match (instance.fields, fields) {
+                (Some(cached), Some(fields)) => core::ptr::eq(cached, fields),
+                (cached, fields) => cached.is_none() && fields.is_none(),
+            };
+            if same {
+                return &instance.names;
+            }
+            next = &instance.next;
+        }
+    }
+}
+
+impl Default for GenericFieldNames {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+pub(crate) use registration::registrations;
+#[cfg(target_has_atomic = "ptr")]
+pub use registration::{register, Registration};
+
+/// Lock-free list of the types registered with `#[fields(register)]`, only
+/// available on targets with pointer-sized atomics.
+#[cfg(target_has_atomic = "ptr")]
+mod registration {
+    use core::ptr;
+    use core::sync::atomic::{AtomicPtr, Ordering};
+
+    use crate::TypeFields;
+
+    /// Type registered with `#[fields(register)]`, a node of the process-wide
+    /// list of registered types.
+    pub struct Registration {
+        type_fields: fn() -> TypeFields,
+        module_path: &'static str,
+        next: AtomicPtr<Registration>,
+    }
+
+    impl Registration {
+        pub const fn new(type_fields: fn() -> TypeFields, module_path: &'static str) -> Self {
+            Self {
+                type_fields,
+                module_path,
+                next: AtomicPtr::new(ptr::null_mut()),
+            }
+        }
+
+        pub(crate) fn type_fields(&self) -> TypeFields {
+            (self.type_fields)().with_module_path(self.module_path)
+        }
+    }
+
+    static REGISTRATIONS: AtomicPtr<Registration> = AtomicPtr::new(ptr::null_mut());
+
+    /// Add a type to the list of registered types. Called once per type by the
+    /// constructors generated for `#[fields(register)]`.
+    pub fn register(registration: &'static Registration) {
+        let node = registration as *const Registration as *mut Registration;
+        let mut head = REGISTRATIONS.load(Ordering::Acquire);
+        loop {
+            registration.next.store(head, Ordering::Relaxed);
+            match REGISTRATIONS.compare_exchange_weak(
+                head,
+                node,
+                Ordering::Release,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Registered types, most recently registered first.
+    pub(crate) fn registrations() -> impl Iterator<Item = &'static Registration> {
+        let head = REGISTRATIONS.load(Ordering::Acquire);
+        // SAFETY: the list only ever contains `&'static Registration`s.
+        core::iter::successors(unsafe { head.as_ref() }, |registration| unsafe {
+            registration.next.load(Ordering::Acquire).as_ref()
+        })
+    }
+}
+
+/// Part of a mask selecting a field, used by derived `ApplyMask`.
+pub enum MaskSelection {
+    /// The field is not selected.
+    None,
+    /// The field is selected as a whole.
+    All,
+    /// Paths below the field are selected, relative to it, together with the
+    /// case their names are re-cased with.
+    Nested(FieldMask, Option<Case>),
+}
+
+/// Selection of the field at `index` of `fields` by `mask`, when the fields
+/// are re-cased with `case`. Paths may select the field by its name or one of
+/// its aliases. Unnamed fields pass the whole mask on.
+pub fn select_field(
+    fields: Option<&'static [Field]>,
+    index: usize,
+    mask: &FieldMask,
+    case: Option<Case>,
+) -> MaskSelection {
+    let field = match fields.and_then(|fields| fields.get(index)) {
+        Some(field) => field,
+        None => return MaskSelection::None,
+    };
+    let nested = match path_names(field, case) {
+        Some(names)
+            if mask
+                .paths()
+                .iter()
+                .any(|path| names.clone().any(|name| path == name)) =>
+        {
+            return MaskSelection::All;
+        }
+        Some(names) => mask
+            .paths()
+            .iter()
+            .filter_map(|path| names.clone().find_map(|name| relative_path(path, name)))
+            .collect(),
+        None => mask.clone(),
+    };
+    if nested.is_empty() {
+        MaskSelection::None
+    } else {
+        MaskSelection::Nested(nested, child_case(field, case))
+    }
+}
+
+/// Collect dotted field paths from field descriptions.
+pub fn field_names(fields: Option<&'static [Field]>) -> Option<FieldNameSet> {
+    collect_paths(fields, &|_, _| true)
+}
+
+/// Fields at a position along a path checked by `path!`, with the case
+/// their names are re-cased with.
+#[derive(Clone, Copy)]
+pub struct PathCursor {
+    fields: Option<&'static [Field]>,
+    case: Option<Case>,
+    provided: bool,
+}
+
+/// Cursor at the top-level `fields` of a type.
+pub const fn path_start(fields: Option<&'static [Field]>) -> PathCursor {
+    PathCursor {
+        fields,
+        case: None,
+        provided: false,
+    }
+}
+
+/// Find the field named `segment` and move the cursor to its nested fields.
+/// Unnamed fields are searched through. Panics with `error` if there is no
+/// such field, which fails compilation when evaluated in a const context.
+/// Paths below fields with a provider can't be checked and panic as well.
+pub const fn path_segment(cursor: PathCursor, segment: &str, error: &'static str) -> PathCursor {
+    if cursor.provided {
+        panic!("field paths below fields provided by a `with` function can't be checked at compile time");
+    }
+    match find_segment(cursor.fields, cursor.case, segment) {
+        Some((field, case)) => PathCursor {
+            fields: field.nested(),
+            case: child_case(field, case),
+            provided: field.provider().is_some(),
+        },
+        None => panic!("{}", error),
+    }
+}
+
+const fn find_segment(
+    fields: Option<&'static [Field]>,
+    case: Option<Case>,
+    segment: &str,
+) -> Option<(&'static Field, Option<Case>)> {
+    let fields = match fields {
+        Some(fields) => fields,
+        None => return None,
+    };
+    let mut i = 0;
+    while i < fields.len() {
+        let field = &fields[i];
+        if field.name().is_some() {
+            let mut sink = Sink::compare(segment.as_bytes());
+            push_name(&mut sink, field, case);
+            if sink.equal && sink.pos == segment.len() {
+                return Some((field, case));
+            }
+        } else if let Some(found) = find_segment(field.nested(), child_case(field, case), segment) {
+            return Some(found);
+        }
+        i += 1;
+    }
+    None
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Destination of field names converted in const contexts, which counts the
+/// bytes of a name and either writes them into `bytes` or compares them with
+/// `expected`.
+struct Sink<'a> {
+    bytes: Option<&'a mut [u8]>,
+    expected: &'a [u8],
+    pos: usize,
+    equal: bool,
+}
+
+impl<'a> Sink<'a> {
+    const fn count() -> Self {
+        Self {
+            bytes: None,
+            expected: &[],
+            pos: 0,
+            equal: false,
+        }
+    }
+
+    const fn write(bytes: &'a mut [u8], pos: usize) -> Self {
+        Self {
+            bytes: Some(bytes),
+            expected: &[],
+            pos,
+            equal: false,
+        }
+    }
+
+    const fn compare(expected: &'a [u8]) -> Self {
+        Self {
+            bytes: None,
+            expected,
+            pos: 0,
+            equal: true,
+        }
+    }
+
+    const fn push(&mut self, byte: u8) {
+        if let Some(bytes) = &mut self.bytes {
+            bytes[self.pos] = byte;
+        }
+        if self.pos >= self.expected.len() || self.expected[self.pos] != byte {
+            self.equal = false;
+        }
+        self.pos += 1;
+    }
+
+    const fn push_str(&mut self, str: &str) {
+        let str = str.as_bytes();
+        let mut i = 0;
+        while i < str.len() {
+            self.push(str[i]);
+            i += 1;
+        }
+    }
+}
+
+/// Push the name of `field`, re-cased with `case` unless the field was
+/// explicitly renamed.
+const fn push_name(sink: &mut Sink, field: &Field, case: Option<Case>) {
+    if let Some(name) = cased_name(field, case) {
+        sink.push_str(name);
+    }
+}
+
+/// A field together with its named ancestors, each with the case its name is
+/// re-cased with.
+struct Prefix<'a> {
+    field: &'static Field,
+    case: Option<Case>,
+    parent: Option<&'a Prefix<'a>>,
+}
+
+/// Panic if the field paths of `fields` can't be computed in const contexts,
+/// because a field has a provider.
+pub const fn check_field_names(fields: Option<&'static [Field]>) {
+    if has_provider(fields) {
+        panic!("`FieldNames` is not available for types with fields provided by a `with` function");
+    }
+}
+
+/// Whether any of `fields`, at any depth, has a provider.
+pub const fn has_provider(fields: Option<&'static [Field]>) -> bool {
+    let fields = match fields {
+        Some(fields) => fields,
+        None => return false,
+    };
+    let mut i = 0;
+    while i < fields.len() {
+        let field = &fields[i];
+        if field.provider().is_some() || has_provider(field.nested()) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Field paths of a type built at compile time from its distinct `paths`
+/// written by [sorted_unique_paths], `None` when it has no `fields`.
+pub const fn static_field_names(
+    fields: Option<&'static [Field]>,
+    paths: &'static [&'static str],
+) -> Option<FieldNameSet> {
+    match fields {
+        Some(_) => Some(FieldSet::from_sorted(paths)),
+        None => None,
+    }
+}
+
+/// Length of the buffer holding all field paths, each followed by a `\0`.
+pub const fn paths_len(fields: Option<&'static [Field]>) -> usize {
+    nested_paths_len(fields, None, None)
+}
+
+const fn nested_paths_len(
+    fields: Option<&'static [Field]>,
+    case: Option<Case>,
+    prefix: Option<&Prefix>,
+) -> usize {
+    let fields = match fields {
+        Some(fields) => fields,
+        None => return 0,
+    };
+    let mut len = 0;
+    let mut i = 0;
+    while i < fields.len() {
+        let field = &fields[i];
+        let nested_case = child_case(field, case);
+        len += match (field.name(), field.nested()) {
+            (Some(_), Some(nested)) => {
+                let prefix = Prefix {
+                    field,
+                    case,
+                    parent: prefix,
+                };
+                nested_paths_len(Some(nested), nested_case, Some(&prefix))
+            }
+            (None, Some(nested)) => nested_paths_len(Some(nested), nested_case, prefix),
+            (Some(_), None) => prefix_len(prefix) + name_len(field, case) + 1,
+            (None, None) => 0,
+        };
+        i += 1;
+    }
+    len
+}
+
+const fn prefix_len(prefix: Option<&Prefix>) -> usize {
+    match prefix {
+        Some(prefix) => prefix_len(prefix.parent) + name_len(prefix.field, prefix.case) + 1,
+        None => 0,
+    }
+}
+
+const fn name_len(field: &Field, case: Option<Case>) -> usize {
+    let mut sink = Sink::count();
+    push_name(&mut sink, field, case);
+    sink.pos
+}
+
+/// Write all field paths, each followed by a `\0`, into a buffer of
+/// [paths_len] bytes.
+pub const fn path_bytes<const LEN: usize>(fields: Option<&'static [Field]>) -> [u8; LEN] {
+    let mut bytes = [0; LEN];
+    let written = write_paths(&mut bytes, 0, fields, None, None);
+    assert!(written == LEN, "field paths do not fit the buffer");
+    bytes
+}
+
+const fn write_paths(
+    bytes: &mut [u8],
+    mut pos: usize,
+    fields: Option<&'static [Field]>,
+    case: Option<Case>,
+    prefix: Option<&Prefix>,
+) -> usize {
+    let fields = match fields {
+        Some(fields) => fields,
+        None => return pos,
+    };
+    let mut i = 0;
+    while i < fields.len() {
+        let field = &fields[i];
+        let nested_case = child_case(field, case);
+        pos = match (field.name(), field.nested()) {
+            (Some(_), Some(nested)) => {
+                let prefix = Prefix {
+                    field,
+                    case,
+                    parent: prefix,
+                };
+                write_paths(bytes, pos, Some(nested), nested_case, Some(&prefix))
+            }
+            (None, Some(nested)) => write_paths(bytes, pos, Some(nested), nested_case, prefix),
+            (Some(_), None) => {
+                pos = write_prefix(bytes, pos, prefix);
+                let mut sink = Sink::write(bytes, pos);
+                push_name(&mut sink, field, case);
+                sink.push(0);
+                sink.pos
+            }
+            (None, None) => pos,
+        };
+        i += 1;
+    }
+    pos
+}
+
+const fn write_prefix(bytes: &mut [u8], mut pos: usize, prefix: Option<&Prefix>) -> usize {
+    if let Some(prefix) = prefix {
+        pos = write_prefix(bytes, pos, prefix.parent);
+        let mut sink = Sink::write(bytes, pos);
+        push_name(&mut sink, prefix.field, prefix.case);
+        sink.push(b'.');
+        pos = sink.pos;
+    }
+    pos
+}
+
+/// Number of distinct paths in a buffer written by [path_bytes].
+pub const fn unique_paths_count<const LEN: usize>(bytes: &[u8; LEN]) -> usize {
+    let (starts, count) = sorted_path_starts(bytes);
+    let mut unique = 0;
+    let mut i = 0;
+    while i < count {
+        if is_first_of_run(bytes, &starts, i) {
+            unique += 1;
+        }
+        i += 1;
+    }
+    unique
+}
+
+/// Distinct paths of a buffer written by [path_bytes], in order of their first
+/// occurrence.
+pub const fn unique_paths<const COUNT: usize, const LEN: usize>(
+    bytes: &'static [u8; LEN],
+) -> [&'static str; COUNT] {
+    let (starts, count) = sorted_path_starts(bytes);
+    let mut first = [false; LEN];
+    let mut i = 0;
+    while i < count {
+        first[starts[i]] = is_first_of_run(bytes, &starts, i);
+        i += 1;
+    }
+
+    let mut paths = [""; COUNT];
+    let mut unique = 0;
+    let mut pos = 0;
+    while pos < LEN {
+        if first[pos] {
+            paths[unique] = path_at(bytes, pos);
+            unique += 1;
+        }
+        pos += path_at(bytes, pos).len() + 1;
+    }
+    paths
+}
+
+/// Distinct paths of a buffer written by [path_bytes], sorted like the paths
+/// of a [FieldSet](crate::FieldSet).
+pub const fn sorted_unique_paths<const COUNT: usize, const LEN: usize>(
+    bytes: &'static [u8; LEN],
+) -> [&'static str; COUNT] {
+    let (starts, count) = sorted_path_starts(bytes);
+    let mut paths = [""; COUNT];
+    let mut unique = 0;
+    let mut i = 0;
+    while i < count {
+        if is_first_of_run(bytes, &starts, i) {
+            paths[unique] = path_at(bytes, starts[i]);
+            unique += 1;
+        }
+        i += 1;
+    }
+    paths
+}
+
+/// Offsets of the paths of a buffer written by [path_bytes] ordered by path,
+/// equal paths by offset, and the number of paths. Sorted with a heapsort, as
+/// the slice sorts aren't available in const fns.
+const fn sorted_path_starts<const LEN: usize>(bytes: &[u8; LEN]) -> ([usize; LEN], usize) {
+    let mut starts = [0; LEN];
+    let mut count = 0;
+    let mut pos = 0;
+    while pos < LEN {
+        starts[count] = pos;
+        count += 1;
+        while bytes[pos] != 0 {
+            pos += 1;
+        }
+        pos += 1;
+    }
+
+    let mut i = count / 2;
+    while i > 0 {
+        i -= 1;
+        sift_down(bytes, &mut starts, i, count);
+    }
+    let mut end = count;
+    while end > 1 {
+        end -= 1;
+        let last = starts[end];
+        starts[end] = starts[0];
+        starts[0] = last;
+        sift_down(bytes, &mut starts, 0, end);
+    }
+    (starts, count)
+}
+
+const fn sift_down(bytes: &[u8], starts: &mut [usize], mut root: usize, end: usize) {
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= end {
+            return;
+        }
+        if child + 1 < end && path_less(bytes, starts[child], starts[child + 1]) {
+            child += 1;
+        }
+        if !path_less(bytes, starts[root], starts[child]) {
+            return;
+        }
+        let parent = starts[root];
+        starts[root] = starts[child];
+        starts[child] = parent;
+        root = child;
+    }
+}
+
+/// Whether the path at offset `a` orders before the path at offset `b`,
+/// comparing the offsets of equal paths.
+const fn path_less(bytes: &[u8], a: usize, b: usize) -> bool {
+    let mut i = 0;
+    loop {
+        if bytes[a + i] != bytes[b + i] {
+            return bytes[a + i] < bytes[b + i];
+        }
+        if bytes[a + i] == 0 {
+            return a < b;
+        }
+        i += 1;
+    }
+}
+
+/// Whether the `i`th of the sorted `starts` is the first of its path.
+const fn is_first_of_run(bytes: &[u8], starts: &[usize], i: usize) -> bool {
+    i == 0
+        || !bytes_eq(
+            path_bytes_at(bytes, starts[i - 1]),
+            path_bytes_at(bytes, starts[i]),
+        )
+}
+
+/// The `\0` terminated path at offset `start`.
+const fn path_bytes_at(bytes: &[u8], start: usize) -> &[u8] {
+    let path = bytes.split_at(start).1;
+    let mut len = 0;
+    while path[len] != 0 {
+        len += 1;
+    }
+    path.split_at(len).0
+}
+
+const fn path_at(bytes: &'static [u8], start: usize) -> &'static str {
+    match core::str::from_utf8(path_bytes_at(bytes, start)) {
+        Ok(path) => path,
+        Err(_) => panic!("field path is not valid UTF-8"),
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{Case, FieldMask};
+
+/// Copy of the fields selected by a [FieldMask] from one value into another,
+/// e.g. for PATCH requests. Derived with `#[fields(apply_mask)]`.
+///
+/// Derived types recurse into fields whose paths are partially selected.
+/// Leaves, collections and other types without their own fields are replaced
+/// as a whole when any path in them is selected. Options selected as a whole
+/// are replaced. When only paths below them are selected, they recurse if
+/// both values are `Some`, take the source if only it is `Some`, and are left
+/// unchanged if the source is `None`, as it has no selected fields to copy.
+pub trait ApplyMask: Sized {
+    /// Move the fields of `source` selected by `mask` into `self`. Paths of
+    /// the mask are paths of [Fields::fields](crate::Fields::fields), whose
+    /// segments may also be aliases.
+    fn apply_mask(&mut self, source: Self, mask: &FieldMask) {
+        self.__apply_mask(source, mask, None)
+    }
+
+    /// [ApplyMask::apply_mask] with the case the names of the fields are
+    /// re-cased with by `rename_all_nested` of a parent.
+    #[doc(hidden)]
+    fn __apply_mask(&mut self, source: Self, mask: &FieldMask, case: Option<Case>);
+}
@@ -0,0 +1,32 @@
+use core::any::type_name;
+
+use crate::{Field, FieldNameSet, Fields};
+
+/// Object-safe counterpart of [Fields], implemented for every type
+/// implementing it, so that fields can be queried through `&dyn DynFields`
+/// or `Box<dyn DynFields>`.
+pub trait DynFields {
+    /// Field paths of the type of the value, see [Fields::fields].
+    fn dyn_fields(&self) -> &'static Option<FieldNameSet>;
+
+    /// Description of the fields of the type of the value, see
+    /// [Fields::FIELDS].
+    fn dyn_field_tree(&self) -> Option<&'static [Field]>;
+
+    /// Full name of the type of the value, e.g. `my_crate::model::User`.
+    fn dyn_type_name(&self) -> &'static str;
+}
+
+impl<T: Fields> DynFields for T {
+    fn dyn_fields(&self) -> &'static Option<FieldNameSet> {
+        T::fields()
+    }
+
+    fn dyn_field_tree(&self) -> Option<&'static [Field]> {
+        T::FIELDS
+    }
+
+    fn dyn_type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+}
@@ -0,0 +1,467 @@
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Case, FieldNameSet, Fields, MetaValue};
+
+/// Compile-time description of a single field of a type implementing
+/// [Fields](crate::Fields).
+///
+/// Descriptions are generated by the derive macro and form a tree: a field
+/// whose type has fields of its own points to their descriptions. Unnamed
+/// fields (e.g. the single field of a newtype enum variant) contribute their
+/// nested fields directly to the parent.
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    name: Option<&'static str>,
+    ident: Option<&'static str>,
+    renamed: bool,
+    cased_names: &'static [&'static str],
+    nested: Option<&'static [Field]>,
+    nested_case: Option<Case>,
+    provider: Option<fn() -> Option<FieldNameSet>>,
+    groups: &'static [&'static str],
+    aliases: &'static [&'static str],
+    deprecated: Option<&'static str>,
+    since: Option<&'static str>,
+    until: Option<&'static str>,
+    doc: Option<&'static str>,
+    sensitive: bool,
+    meta: &'static [(&'static str, MetaValue)],
+    profiles: &'static [(&'static str, &'static str)],
+}
+
+impl Field {
+    /// Describe a named field.
+    pub const fn named(name: &'static str) -> Self {
+        Self {
+            name: Some(name),
+            ident: None,
+            renamed: false,
+            cased_names: &[],
+            nested: None,
+            nested_case: None,
+            provider: None,
+            groups: &[],
+            aliases: &[],
+            deprecated: None,
+            since: None,
+            until: None,
+            doc: None,
+            sensitive: false,
+            meta: &[],
+            profiles: &[],
+        }
+    }
+
+    /// Describe an unnamed field.
+    pub const fn unnamed() -> Self {
+        Self {
+            name: None,
+            ident: None,
+            renamed: false,
+            cased_names: &[],
+            nested: None,
+            nested_case: None,
+            provider: None,
+            groups: &[],
+            aliases: &[],
+            deprecated: None,
+            since: None,
+            until: None,
+            doc: None,
+            sensitive: false,
+            meta: &[],
+            profiles: &[],
+        }
+    }
+
+    /// Set the identifier of the field when it differs from its name.
+    pub const fn with_ident(mut self, ident: &'static str) -> Self {
+        self.ident = Some(ident);
+        self
+    }
+
+    /// Mark the field as explicitly renamed.
+    pub const fn with_renamed(mut self, renamed: bool) -> Self {
+        self.renamed = renamed;
+        self
+    }
+
+    /// Set the identifier of the field converted to every path-safe [Case],
+    /// in the order of [Case::ALL], used when a parent re-cases the field
+    /// with `rename_all_nested`.
+    #[doc(hidden)]
+    pub const fn with_cased_names(mut self, cased_names: &'static [&'static str]) -> Self {
+        self.cased_names = cased_names;
+        self
+    }
+
+    /// Set the fields of the field's type.
+    pub const fn with_nested(mut self, nested: Option<&'static [Field]>) -> Self {
+        self.nested = nested;
+        self
+    }
+
+    /// Set the fields of the type `T`, or take its paths from
+    /// [Fields::fields] at runtime if [Fields::FIELDS] doesn't describe them.
+    #[doc(hidden)]
+    pub const fn with_nested_type<T: Fields>(self) -> Self {
+        if T::RUNTIME_FIELDS {
+            self.with_provider(T::field_set)
+        } else {
+            self.with_nested(T::FIELDS)
+        }
+    }
+
+    /// Set a function returning the nested field paths at runtime, used in
+    /// place of [nested](Self::nested) fields.
+    pub const fn with_provider(mut self, provider: fn() -> Option<FieldNameSet>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Re-case the names of all fields below the field, at any depth, except
+    /// explicitly renamed ones.
+    pub const fn with_nested_case(mut self, case: Case) -> Self {
+        self.nested_case = Some(case);
+        self
+    }
+
+    /// Set the groups the field belongs to.
+    pub const fn with_groups(mut self, groups: &'static [&'static str]) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Set alternative names accepted in place of the field's name.
+    pub const fn with_aliases(mut self, aliases: &'static [&'static str]) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Mark the field as deprecated for `reason`, which may be empty.
+    pub const fn with_deprecated(mut self, reason: &'static str) -> Self {
+        self.deprecated = Some(reason);
+        self
+    }
+
+    /// Set the version the field was introduced in.
+    pub const fn with_since(mut self, version: &'static str) -> Self {
+        self.since = Some(version);
+        self
+    }
+
+    /// Set the version the field was removed in.
+    pub const fn with_until(mut self, version: &'static str) -> Self {
+        self.until = Some(version);
+        self
+    }
+
+    /// Set the description of the field.
+    pub const fn with_doc(mut self, doc: &'static str) -> Self {
+        self.doc = Some(doc);
+        self
+    }
+
+    /// Mark the field as holding sensitive data.
+    pub const fn with_sensitive(mut self, sensitive: bool) -> Self {
+        self.sensitive = sensitive;
+        self
+    }
+
+    /// Set user metadata of the field.
+    pub const fn with_meta(mut self, meta: &'static [(&'static str, MetaValue)]) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Set the names of the field in naming profiles, as `(profile, name)`
+    /// pairs.
+    pub const fn with_profiles(
+        mut self,
+        profiles: &'static [(&'static str, &'static str)],
+    ) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    /// Name of the field after renames, `None` for unnamed fields.
+    pub const fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// Identifier of the field in Rust source, without the `r#` prefix of raw
+    /// identifiers. `None` for unnamed fields.
+    pub const fn ident(&self) -> Option<&'static str> {
+        match self.ident {
+            Some(ident) => Some(ident),
+            None => self.name,
+        }
+    }
+
+    /// Whether the field was explicitly renamed with `rename`.
+    pub const fn is_renamed(&self) -> bool {
+        self.renamed
+    }
+
+    /// Name of the field when a parent re-cases it with `case`. Explicitly
+    /// renamed fields, and fields without names converted to `case`, keep
+    /// their [name](Self::name).
+    pub const fn cased_name(&self, case: Case) -> Option<&'static str> {
+        let index = case as usize;
+        if self.name.is_some() && !self.renamed && index < self.cased_names.len() {
+            Some(self.cased_names[index])
+        } else {
+            self.name
+        }
+    }
+
+    /// Name of the field in the naming `profile`, falling back to
+    /// [name](Self::name) when the profile doesn't rename the field.
+    pub fn name_for(&self, profile: &str) -> Option<&'static str> {
+        let name = self.name?;
+        Some(
+            self.profiles
+                .iter()
+                .find(|(candidate, _)| *candidate == profile)
+                .map_or(name, |(_, renamed)| renamed),
+        )
+    }
+
+    /// Names of the field in naming profiles, as `(profile, name)` pairs.
+    pub const fn profiles(&self) -> &'static [(&'static str, &'static str)] {
+        self.profiles
+    }
+
+    /// Fields of the field's type, `None` if the type has no fields or
+    /// nesting was disabled.
+    pub const fn nested(&self) -> Option<&'static [Field]> {
+        self.nested
+    }
+
+    /// Function returning the nested field paths at runtime, set with a
+    /// `with` function.
+    pub const fn provider(&self) -> Option<fn() -> Option<FieldNameSet>> {
+        self.provider
+    }
+
+    /// Case applied to the names of all fields below the field, set with
+    /// `rename_all_nested`.
+    pub const fn nested_case(&self) -> Option<Case> {
+        self.nested_case
+    }
+
+    /// Groups the field belongs to.
+    pub const fn groups(&self) -> &'static [&'static str] {
+        self.groups
+    }
+
+    /// Alternative names accepted in place of the field's name.
+    pub const fn aliases(&self) -> &'static [&'static str] {
+        self.aliases
+    }
+
+    /// Reason of the deprecation, `None` if the field is not deprecated.
+    pub const fn deprecated(&self) -> Option<&'static str> {
+        self.deprecated
+    }
+
+    /// Version the field was introduced in.
+    pub const fn since(&self) -> Option<&'static str> {
+        self.since
+    }
+
+    /// Version the field was removed in, the first version without it.
+    pub const fn until(&self) -> Option<&'static str> {
+        self.until
+    }
+
+    /// Description of the field, taken from its doc comment.
+    pub const fn doc(&self) -> Option<&'static str> {
+        self.doc
+    }
+
+    /// Whether the field holds sensitive data. Fields nested in a sensitive
+    /// field are sensitive as well.
+    pub const fn is_sensitive(&self) -> bool {
+        self.sensitive
+    }
+
+    /// User metadata of the field, including the defaults set on the type
+    /// containing it.
+    pub const fn meta(&self) -> &'static [(&'static str, MetaValue)] {
+        self.meta
+    }
+}
+
+/// Name of `field` when a parent re-cases the fields below it with `case`.
+/// Explicitly renamed fields keep their names.
+pub(crate) const fn cased_name(field: &Field, case: Option<Case>) -> Option<&'static str> {
+    match case {
+        Some(case) => field.cased_name(case),
+        None => field.name(),
+    }
+}
+
+/// Case applying to the names of the fields below `field`, when the fields
+/// above re-case their descendants with `case`. The innermost case wins.
+pub(crate) const fn child_case(field: &Field, case: Option<Case>) -> Option<Case> {
+    match field.nested_case() {
+        Some(case) => Some(case),
+        None => case,
+    }
+}
+
+/// Collect dotted paths of the fields accepted by `filter`, which receives the
+/// field together with its siblings. Nested fields are only visited when
+/// their parent is accepted.
+pub(crate) fn collect_paths<F>(fields: Option<&'static [Field]>, filter: &F) -> Option<FieldNameSet>
+where
+    F: Fn(&'static [Field], &Field) -> bool,
+{
+    collect_named_paths(fields, filter, &|field, case| {
+        cased_name(field, case).map(Cow::Borrowed)
+    })
+}
+
+/// Like [collect_paths], with segment names given by `name`, which receives
+/// the case the field's parents re-case it with.
+pub(crate) fn collect_named_paths<F, N>(
+    fields: Option<&'static [Field]>,
+    filter: &F,
+    name: &N,
+) -> Option<FieldNameSet>
+where
+    F: Fn(&'static [Field], &Field) -> bool,
+    N: Fn(&Field, Option<Case>) -> Option<Cow<'static, str>>,
+{
+    collect_cased_paths(fields, None, filter, name)
+}
+
+fn collect_cased_paths<F, N>(
+    fields: Option<&'static [Field]>,
+    case: Option<Case>,
+    filter: &F,
+    name: &N,
+) -> Option<FieldNameSet>
+where
+    F: Fn(&'static [Field], &Field) -> bool,
+    N: Fn(&Field, Option<Case>) -> Option<Cow<'static, str>>,
+{
+    let fields = fields?;
+    let mut paths = FieldNameSet::new();
+    for field in fields.iter().filter(|field| filter(fields, field)) {
+        let nested = match field.provider() {
+            Some(provider) => provider(),
+            None => collect_cased_paths(field.nested(), child_case(field, case), filter, name),
+        };
+        match (name(field, case), nested) {
+            (Some(name), Some(nested)) => {
+                paths.extend(nested.iter().map(|nested| format!("{}.{}", name, nested)))
+            }
+            (None, Some(nested)) => paths.extend(nested),
+            (Some(name), None) => {
+                paths.insert(name.into_owned());
+            }
+            (None, None) => {}
+        }
+    }
+    Some(paths)
+}
+
+/// Fields along a dotted `path` whose segments may be names or aliases.
+/// Unnamed fields are searched through. Paths below a field with a provider
+/// end at that field.
+pub(crate) fn fields_along(
+    fields: Option<&'static [Field]>,
+    path: &str,
+) -> Option<Vec<&'static Field>> {
+    let (along, _) = cased_fields_along(fields, path)?;
+    Some(along.into_iter().map(|(field, _)| field).collect())
+}
+
+/// Field at a dotted `path`, `None` if the path doesn't exist or is below a
+/// field with a provider.
+pub(crate) fn field_at(fields: Option<&'static [Field]>, path: &str) -> Option<&'static Field> {
+    match cased_fields_along(fields, path)? {
+        (mut along, None) => along.pop().map(|(field, _)| field),
+        (_, Some(_)) => None,
+    }
+}
+
+/// Canonical form of a dotted `path` whose segments may be names or aliases.
+pub(crate) fn canonical_path(fields: Option<&'static [Field]>, path: &str) -> Option<String> {
+    let (along, rest) = cased_fields_along(fields, path)?;
+    let names: Option<Vec<_>> = along
+        .into_iter()
+        .map(|(field, case)| cased_name(field, case))
+        .chain(rest.map(Some))
+        .collect();
+    Some(names?.join("."))
+}
+
+/// Field together with the case its parents re-case it with.
+type CasedField = (&'static Field, Option<Case>);
+
+/// Fields along a dotted `path` and the rest of the path below a field with a
+/// provider.
+fn cased_fields_along<'p>(
+    fields: Option<&'static [Field]>,
+    path: &'p str,
+) -> Option<(Vec<CasedField>, Option<&'p str>)> {
+    let mut fields = fields;
+    let mut case = None;
+    let mut along = Vec::new();
+    let mut rest = Some(path);
+    while let Some(remaining) = rest {
+        let (segment, next) = match remaining.split_once('.') {
+            Some((segment, next)) => (segment, Some(next)),
+            None => (remaining, None),
+        };
+        let (field, field_case) = find_field(fields?, case, segment)?;
+        along.push((field, field_case));
+        rest = next;
+        if let Some(provider) = field.provider() {
+            return match rest {
+                Some(rest) if !is_provided(provider, rest) => None,
+                rest => Some((along, rest)),
+            };
+        }
+        fields = field.nested();
+        case = child_case(field, field_case);
+    }
+    Some((along, None))
+}
+
+/// Whether `path` is one of the paths returned by `provider` or a prefix of
+/// one of them.
+fn is_provided(provider: fn() -> Option<FieldNameSet>, path: &str) -> bool {
+    provider().is_some_and(|paths| {
+        paths.iter().any(|provided| {
+            provided
+                .strip_prefix(path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    })
+}
+
+fn find_field(fields: &'static [Field], case: Option<Case>, segment: &str) -> Option<CasedField> {
+    fields
+        .iter()
+        .find_map(|field| match path_names(field, case) {
+            Some(mut names) => names.any(|name| name == segment).then_some((field, case)),
+            None => find_field(field.nested()?, child_case(field, case), segment),
+        })
+}
+
+/// Names selecting `field` in a path when a parent re-cases it with `case`:
+/// its name followed by its aliases, `None` for unnamed fields.
+pub(crate) fn path_names(
+    field: &Field,
+    case: Option<Case>,
+) -> Option<impl Iterator<Item = &'static str> + Clone> {
+    let name = cased_name(field, case)?;
+    Some(core::iter::once(name).chain(field.aliases().iter().copied()))
+}
@@ -0,0 +1,288 @@
+use core::fmt;
+use core::iter::FromIterator;
+use core::str::FromStr;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::field_set::{is_under, relative_path};
+use crate::{FieldSet, Fields};
+
+/// Set of field paths selected by an update or read mask, e.g. the
+/// `update_mask` of a request, with the semantics of protobuf's `FieldMask`.
+///
+/// ```rust
+/// use fields::{FieldMask, Fields};
+///
+/// #[derive(Fields)]
+/// struct User {
+///     id: u64,
+///     address: Address,
+/// }
+///
+/// #[derive(Fields)]
+/// struct Address {
+///     city: String,
+///     street: String,
+/// }
+///
+/// let mask: FieldMask = "address.city, address".parse().unwrap();
+/// assert_eq!(mask.normalize().to_string(), "address");
+/// assert_eq!(mask.canonicalize::<User>().unwrap().to_string(), "address.city,address.street");
+/// assert!(mask.validate::<User>().is_ok());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct FieldMask {
+    paths: Vec<String>,
+}
+
+impl FieldMask {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse comma-separated paths, e.g. `a,b.c`. Whitespace around paths
+    /// and empty entries are ignored, and paths are kept in the given order.
+    pub fn parse(mask: &str) -> Result<Self, FieldMaskError> {
+        mask.split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(|path| {
+                if is_valid_path(path) {
+                    Ok(path.to_string())
+                } else {
+                    Err(FieldMaskError::InvalidPath(path.to_string()))
+                }
+            })
+            .collect()
+    }
+
+    /// Parse the protobuf JSON representation of a mask, whose paths are in
+    /// lowerCamelCase, converting them to snake_case.
+    pub fn from_json_string(mask: &str) -> Result<Self, FieldMaskError> {
+        Self::parse(mask)?
+            .paths
+            .iter()
+            .map(|path| {
+                camel_to_snake(path).ok_or_else(|| FieldMaskError::InvalidJsonPath(path.clone()))
+            })
+            .collect()
+    }
+
+    /// Protobuf JSON representation of the mask, with the paths converted
+    /// from snake_case to lowerCamelCase. Fails for paths that wouldn't
+    /// convert back to the same path, e.g. paths with uppercase letters.
+    pub fn to_json_string(&self) -> Result<String, FieldMaskError> {
+        let paths = self
+            .paths
+            .iter()
+            .map(|path| {
+                snake_to_camel(path).ok_or_else(|| FieldMaskError::InvalidJsonPath(path.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(paths.join(","))
+    }
+
+    /// Paths in the order they were given.
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Whether `path` is selected by the mask, i.e. the mask contains it or
+    /// any of its parents.
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths.iter().any(|selected| is_under(path, selected))
+    }
+
+    /// Paths below `prefix`, relative to it, e.g. `city` for `address.city`
+    /// and the prefix `address`. An empty prefix keeps all paths.
+    pub fn strip_prefix(&self, prefix: &str) -> FieldMask {
+        self.paths
+            .iter()
+            .filter_map(|path| relative_path(path, prefix))
+            .collect()
+    }
+
+    /// Check that every path exists in `T`, as a field or a parent of
+    /// fields. Paths may use aliases.
+    pub fn validate<T: Fields>(&self) -> Result<(), FieldMaskError> {
+        self.canonical_paths::<T>().map(|_| ())
+    }
+
+    /// Sorted paths without duplicates and without paths whose parent is in
+    /// the mask. Paths are compared as written, so a path through an alias
+    /// is kept under its selected parent, e.g. `address,home.city` when `home`
+    /// is an alias of `address`; use [canonicalize](Self::canonicalize) to
+    /// resolve aliases first.
+    pub fn normalize(&self) -> FieldMask {
+        self.to_field_set().roots().into()
+    }
+
+    /// Validated and normalized mask with aliases resolved and every path
+    /// expanded to the paths of `T::fields()` below it, e.g. `address` to
+    /// `address.city,address.street`.
+    pub fn canonicalize<T: Fields>(&self) -> Result<FieldMask, FieldMaskError> {
+        let fields = T::field_set().unwrap_or_default();
+        let roots = self.canonical_paths::<T>()?.roots();
+        let mut expanded = FieldSet::new();
+        for path in roots.iter() {
+            let leaves = fields.subtree(path);
+            if leaves.is_empty() {
+                expanded.insert(path);
+            } else {
+                expanded.extend(leaves);
+            }
+        }
+        Ok(expanded.into())
+    }
+
+    /// Normalized mask selecting the paths selected by either mask.
+    pub fn union(&self, other: &FieldMask) -> FieldMask {
+        (&self.to_field_set() | &other.to_field_set())
+            .roots()
+            .into()
+    }
+
+    /// Normalized mask selecting the paths selected by both masks, e.g. `a.b`
+    /// for `a` and `a.b,c`.
+    pub fn intersection(&self, other: &FieldMask) -> FieldMask {
+        let (this, other) = (self.normalize(), other.normalize());
+        let paths = this.paths.iter().flat_map(|path| {
+            other
+                .paths
+                .iter()
+                .filter(move |selected| is_under(selected, path) || is_under(path, selected))
+                .map(move |selected| {
+                    if is_under(selected, path) {
+                        selected
+                    } else {
+                        path
+                    }
+                })
+        });
+        FieldSet::from_iter(paths.cloned()).roots().into()
+    }
+
+    /// Paths of the mask as a [FieldSet].
+    pub fn to_field_set(&self) -> FieldSet {
+        self.paths.iter().map(String::as_str).collect()
+    }
+
+    fn canonical_paths<T: Fields>(&self) -> Result<FieldSet, FieldMaskError> {
+        self.paths
+            .iter()
+            .map(|path| {
+                T::canonical_path(path).ok_or_else(|| FieldMaskError::UnknownPath(path.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Whether every segment of `path` is non-empty and free of whitespace.
+fn is_valid_path(path: &str) -> bool {
+    path.split('.')
+        .all(|segment| !segment.is_empty() && !segment.contains(char::is_whitespace))
+}
+
+/// `foo_bar.baz` to `fooBar.baz`, `None` if the result wouldn't convert back,
+/// i.e. for uppercase letters or `_` not followed by a lowercase letter.
+fn snake_to_camel(path: &str) -> Option<String> {
+    let mut camel = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '_' => match chars.next() {
+                Some(next) if next.is_ascii_lowercase() => camel.push(next.to_ascii_uppercase()),
+                _ => return None,
+            },
+            c if c.is_ascii_uppercase() => return None,
+            c => camel.push(c),
+        }
+    }
+    Some(camel)
+}
+
+/// `fooBar.baz` to `foo_bar.baz`, `None` for paths containing `_`.
+fn camel_to_snake(path: &str) -> Option<String> {
+    let mut snake = String::with_capacity(path.len() + 4);
+    for c in path.chars() {
+        match c {
+            '_' => return None,
+            c if c.is_ascii_uppercase() => {
+                snake.push('_');
+                snake.push(c.to_ascii_lowercase());
+            }
+            c => snake.push(c),
+        }
+    }
+    Some(snake)
+}
+
+impl FromStr for FieldMask {
+    type Err = FieldMaskError;
+
+    fn from_str(mask: &str) -> Result<Self, Self::Err> {
+        Self::parse(mask)
+    }
+}
+
+/// Paths separated by commas, e.g. `a,b.c`.
+impl fmt::Display for FieldMask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.paths.join(","))
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for FieldMask {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self {
+            paths: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<FieldSet> for FieldMask {
+    fn from(set: FieldSet) -> Self {
+        set.into_iter().collect()
+    }
+}
+
+impl From<FieldMask> for FieldSet {
+    fn from(mask: FieldMask) -> Self {
+        mask.paths.into_iter().collect()
+    }
+}
+
+/// Error of parsing, validating or converting a [FieldMask].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldMaskError {
+    /// The path has an empty segment or whitespace within it.
+    InvalidPath(String),
+    /// The path is not a field of the type the mask is validated against.
+    UnknownPath(String),
+    /// The path can't be converted between snake_case and lowerCamelCase
+    /// without loss.
+    InvalidJsonPath(String),
+}
+
+impl fmt::Display for FieldMaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldMaskError::InvalidPath(path) => write!(f, "invalid field mask path `{}`", path),
+            FieldMaskError::UnknownPath(path) => write!(f, "unknown field mask path `{}`", path),
+            FieldMaskError::InvalidJsonPath(path) => {
+                write!(f, "field mask path `{}` has no JSON representation", path)
+            }
+        }
+    }
+}
+
+impl core::error::Error for FieldMaskError {}
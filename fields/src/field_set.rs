@@ -0,0 +1,475 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::ops::{BitAnd, BitOr, BitXor, Bound, Sub};
+use core::slice;
+
+use alloc::collections::{btree_set, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+/// Sorted set of dotted field paths with set algebra and operations on the
+/// subtrees below a path, returned by [Fields::fields](crate::Fields::fields).
+/// Sets built at compile time borrow their paths until they are modified.
+///
+/// ```rust
+/// use fields::FieldSet;
+///
+/// let set: FieldSet = ["id", "address.city", "address.street"].into_iter().collect();
+/// assert_eq!(set.subtree("address").len(), 2);
+/// assert_eq!(set.strip_prefix("address").iter().collect::<Vec<_>>(), ["city", "street"]);
+/// assert_eq!(set.collapse("address").iter().collect::<Vec<_>>(), ["address", "id"]);
+/// ```
+#[derive(Clone)]
+pub struct FieldSet {
+    paths: Paths,
+}
+
+/// Paths of a [FieldSet], either sorted and distinct paths built at compile
+/// time or paths owned by the set.
+#[derive(Clone)]
+enum Paths {
+    Static(&'static [&'static str]),
+    Owned(BTreeSet<String>),
+}
+
+impl FieldSet {
+    /// Empty set.
+    pub const fn new() -> Self {
+        Self {
+            paths: Paths::Static(&[]),
+        }
+    }
+
+    /// Set borrowing `paths`, which have to be sorted and distinct.
+    pub(crate) const fn from_sorted(paths: &'static [&'static str]) -> Self {
+        Self {
+            paths: Paths::Static(paths),
+        }
+    }
+
+    /// Number of paths in the set.
+    pub fn len(&self) -> usize {
+        match &self.paths {
+            Paths::Static(paths) => paths.len(),
+            Paths::Owned(paths) => paths.len(),
+        }
+    }
+
+    /// Whether the set contains no paths.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the set contains exactly `path`, not counting paths below it.
+    pub fn contains(&self, path: &str) -> bool {
+        match &self.paths {
+            Paths::Static(paths) => paths.binary_search(&path).is_ok(),
+            Paths::Owned(paths) => paths.contains(path),
+        }
+    }
+
+    /// Add a path, returning whether it wasn't in the set yet.
+    pub fn insert(&mut self, path: impl Into<String>) -> bool {
+        self.to_mut().insert(path.into())
+    }
+
+    /// Remove a path, returning whether it was in the set.
+    pub fn remove(&mut self, path: &str) -> bool {
+        self.contains(path) && self.to_mut().remove(path)
+    }
+
+    /// Paths in lexicographic order.
+    pub fn iter(&self) -> Iter<'_> {
+        let paths = match &self.paths {
+            Paths::Static(paths) => IterPaths::Static(paths.iter()),
+            Paths::Owned(paths) => IterPaths::Owned(paths.iter()),
+        };
+        Iter { paths }
+    }
+
+    /// Paths in either set.
+    pub fn union(&self, other: &FieldSet) -> FieldSet {
+        self.iter().chain(other.iter()).collect()
+    }
+
+    /// Paths in both sets.
+    pub fn intersection(&self, other: &FieldSet) -> FieldSet {
+        self.iter().filter(|path| other.contains(path)).collect()
+    }
+
+    /// Paths in this set but not in `other`.
+    pub fn difference(&self, other: &FieldSet) -> FieldSet {
+        self.iter().filter(|path| !other.contains(path)).collect()
+    }
+
+    /// Paths in exactly one of the sets.
+    pub fn symmetric_difference(&self, other: &FieldSet) -> FieldSet {
+        self.difference(other)
+            .iter()
+            .chain(other.iter().filter(|path| !self.contains(path)))
+            .collect()
+    }
+
+    /// Whether every path of this set is in `other`.
+    pub fn is_subset(&self, other: &FieldSet) -> bool {
+        self.len() <= other.len() && self.iter().all(|path| other.contains(path))
+    }
+
+    /// Whether every path of `other` is in this set.
+    pub fn is_superset(&self, other: &FieldSet) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Whether the set contains `prefix` or any path below it.
+    pub fn contains_subtree(&self, prefix: &str) -> bool {
+        self.under(prefix).next().is_some()
+    }
+
+    /// Paths equal to `prefix` or below it, e.g. `address` and `address.city`
+    /// for the prefix `address`, but not `addresses`. An empty prefix matches
+    /// all paths.
+    pub fn subtree(&self, prefix: &str) -> FieldSet {
+        self.under(prefix).collect()
+    }
+
+    /// Paths below `prefix`, relative to it, e.g. `city` for `address.city`
+    /// and the prefix `address`.
+    pub fn strip_prefix(&self, prefix: &str) -> FieldSet {
+        self.under(prefix)
+            .filter_map(|path| relative_path(path, prefix))
+            .collect()
+    }
+
+    /// All paths nested under `prefix`, e.g. `address.city` for `city` and the
+    /// prefix `address`.
+    pub fn with_prefix(&self, prefix: &str) -> FieldSet {
+        if prefix.is_empty() {
+            return self.clone();
+        }
+        self.iter()
+            .map(|path| format!("{}.{}", prefix, path))
+            .collect()
+    }
+
+    /// Replace the paths below `prefix` with `prefix` itself. The set is left
+    /// unchanged if it contains neither `prefix` nor paths below it.
+    pub fn collapse(&self, prefix: &str) -> FieldSet {
+        if prefix.is_empty() || !self.contains_subtree(prefix) {
+            return self.clone();
+        }
+        let mut collapsed: FieldSet = self.iter().filter(|path| !is_under(path, prefix)).collect();
+        collapsed.insert(prefix);
+        collapsed
+    }
+
+    /// Paths without any of their parents in the set, collapsing the
+    /// children of every path present into it.
+    pub fn roots(&self) -> FieldSet {
+        self.iter()
+            .filter(|path| !parents(path).any(|parent| self.contains(parent)))
+            .collect()
+    }
+
+    fn under<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        let from = match &self.paths {
+            Paths::Static(paths) => {
+                Tail::Static(paths[paths.partition_point(|path| *path < prefix)..].iter())
+            }
+            Paths::Owned(paths) => {
+                Tail::Owned(paths.range::<str, _>((Bound::Included(prefix), Bound::Unbounded)))
+            }
+        };
+        from.take_while(move |path| path.starts_with(prefix))
+            .filter(move |path| is_under(path, prefix))
+    }
+
+    /// Paths owned by the set, copied from the borrowed ones first.
+    fn to_mut(&mut self) -> &mut BTreeSet<String> {
+        if let Paths::Static(paths) = self.paths {
+            self.paths = Paths::Owned(paths.iter().map(|path| path.to_string()).collect());
+        }
+        match &mut self.paths {
+            Paths::Owned(paths) => paths,
+            Paths::Static(_) => unreachable!(),
+        }
+    }
+}
+
+/// Whether `path` is `prefix` or a path below it.
+pub(crate) fn is_under(path: &str, prefix: &str) -> bool {
+    prefix.is_empty()
+        || match path.strip_prefix(prefix) {
+            Some(rest) => rest.is_empty() || rest.starts_with('.'),
+            None => false,
+        }
+}
+
+/// `path` relative to `prefix`, e.g. `city` for `address.city` and the prefix
+/// `address`, or `None` if `path` isn't below `prefix`.
+pub(crate) fn relative_path<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    if prefix.is_empty() {
+        return Some(path);
+    }
+    path.strip_prefix(prefix)?.strip_prefix('.')
+}
+
+/// Proper parents of `path`, e.g. `a` and `a.b` for `a.b.c`.
+fn parents(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices('.').map(move |(i, _)| &path[..i])
+}
+
+impl Default for FieldSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for FieldSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for FieldSet {
+    fn eq(&self, other: &FieldSet) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for FieldSet {}
+
+impl PartialOrd for FieldSet {
+    fn partial_cmp(&self, other: &FieldSet) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FieldSet {
+    fn cmp(&self, other: &FieldSet) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl Hash for FieldSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for path in self.iter() {
+            path.hash(state);
+        }
+    }
+}
+
+/// Iterator over the paths of a [FieldSet] in lexicographic order.
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    paths: IterPaths<'a>,
+}
+
+#[derive(Debug, Clone)]
+enum IterPaths<'a> {
+    Static(slice::Iter<'a, &'static str>),
+    Owned(btree_set::Iter<'a, String>),
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.paths {
+            IterPaths::Static(paths) => paths.next().copied(),
+            IterPaths::Owned(paths) => paths.next().map(String::as_str),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.paths {
+            IterPaths::Static(paths) => paths.size_hint(),
+            IterPaths::Owned(paths) => paths.size_hint(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.paths {
+            IterPaths::Static(paths) => paths.next_back().copied(),
+            IterPaths::Owned(paths) => paths.next_back().map(String::as_str),
+        }
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a> IntoIterator for &'a FieldSet {
+    type Item = &'a str;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for FieldSet {
+    type Item = String;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let paths = match self.paths {
+            Paths::Static(paths) => IntoIterPaths::Static(paths.iter()),
+            Paths::Owned(paths) => IntoIterPaths::Owned(paths.into_iter()),
+        };
+        IntoIter { paths }
+    }
+}
+
+/// Owning iterator over the paths of a [FieldSet] in lexicographic order.
+#[derive(Debug)]
+pub struct IntoIter {
+    paths: IntoIterPaths,
+}
+
+#[derive(Debug)]
+enum IntoIterPaths {
+    Static(slice::Iter<'static, &'static str>),
+    Owned(btree_set::IntoIter<String>),
+}
+
+impl Iterator for IntoIter {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.paths {
+            IntoIterPaths::Static(paths) => paths.next().map(|path| path.to_string()),
+            IntoIterPaths::Owned(paths) => paths.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.paths {
+            IntoIterPaths::Static(paths) => paths.size_hint(),
+            IntoIterPaths::Owned(paths) => paths.size_hint(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.paths {
+            IntoIterPaths::Static(paths) => paths.next_back().map(|path| path.to_string()),
+            IntoIterPaths::Owned(paths) => paths.next_back(),
+        }
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+/// Paths from a lower bound on, in lexicographic order.
+enum Tail<'a> {
+    Static(slice::Iter<'a, &'static str>),
+    Owned(btree_set::Range<'a, String>),
+}
+
+impl<'a> Iterator for Tail<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Tail::Static(paths) => paths.next().copied(),
+            Tail::Owned(paths) => paths.next().map(String::as_str),
+        }
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for FieldSet {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self {
+            paths: Paths::Owned(iter.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+impl<S: Into<String>> Extend<S> for FieldSet {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        self.to_mut().extend(iter.into_iter().map(Into::into));
+    }
+}
+
+impl From<BTreeSet<String>> for FieldSet {
+    fn from(paths: BTreeSet<String>) -> Self {
+        Self {
+            paths: Paths::Owned(paths),
+        }
+    }
+}
+
+impl From<FieldSet> for BTreeSet<String> {
+    fn from(set: FieldSet) -> Self {
+        match set.paths {
+            Paths::Owned(paths) => paths,
+            Paths::Static(_) => set.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H> From<HashSet<String, H>> for FieldSet {
+    fn from(paths: HashSet<String, H>) -> Self {
+        paths.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: core::hash::BuildHasher + Default> From<FieldSet> for HashSet<String, H> {
+    fn from(set: FieldSet) -> Self {
+        set.into_iter().collect()
+    }
+}
+
+/// Paths separated by commas, e.g. `address.city,id`.
+impl fmt::Display for FieldSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, path) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl BitOr for &FieldSet {
+    type Output = FieldSet;
+
+    fn bitor(self, other: &FieldSet) -> FieldSet {
+        self.union(other)
+    }
+}
+
+impl BitAnd for &FieldSet {
+    type Output = FieldSet;
+
+    fn bitand(self, other: &FieldSet) -> FieldSet {
+        self.intersection(other)
+    }
+}
+
+impl Sub for &FieldSet {
+    type Output = FieldSet;
+
+    fn sub(self, other: &FieldSet) -> FieldSet {
+        self.difference(other)
+    }
+}
+
+impl BitXor for &FieldSet {
+    type Output = FieldSet;
+
+    fn bitxor(self, other: &FieldSet) -> FieldSet {
+        self.symmetric_difference(other)
+    }
+}
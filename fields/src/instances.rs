@@ -1,83 +1,167 @@
-use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
 
-use crate::Fields;
+use crate::{ApplyMask, Case, Field, FieldMask, FieldNameSet, Fields};
 
 macro_rules! impl_none_fields {
     ($type_:ident) => {
         impl Fields for $type_ {
-            fn fields() -> &'static Option<HashSet<String>> {
+            const FIELDS: Option<&'static [Field]> = None;
+            const RUNTIME_FIELDS: bool = false;
+
+            fn fields() -> &'static Option<FieldNameSet> {
                 &None
             }
         }
     };
 }
 
+/// `ApplyMask` replacing the whole value when anything in it is selected.
+macro_rules! impl_replace_apply_mask {
+    ($type_:ty $(, $param:ident)*) => {
+        impl<$($param),*> ApplyMask for $type_ {
+            fn __apply_mask(&mut self, source: Self, mask: &FieldMask, _: Option<Case>) {
+                if !mask.is_empty() {
+                    *self = source;
+                }
+            }
+        }
+    };
+}
+
 impl<T: Fields> Fields for Option<T> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
 impl<T: Fields, E> Fields for Result<T, E> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
 impl<T: Fields> Fields for Vec<T> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
 impl<T: Fields> Fields for VecDeque<T> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
 impl<T: Fields> Fields for LinkedList<T> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
+#[cfg(feature = "std")]
 impl<K, V: Fields> Fields for HashMap<K, V> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = V::FIELDS;
+    const RUNTIME_FIELDS: bool = V::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         V::fields()
     }
 }
 
 impl<K, V: Fields> Fields for BTreeMap<K, V> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = V::FIELDS;
+    const RUNTIME_FIELDS: bool = V::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         V::fields()
     }
 }
 
+#[cfg(feature = "std")]
 impl<T: Fields, S> Fields for HashSet<T, S> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
 impl<T: Fields> Fields for BTreeSet<T> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
 impl<T: Fields> Fields for BinaryHeap<T> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
 impl<T: Fields> Fields for Box<T> {
-    fn fields() -> &'static Option<std::collections::HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = T::FIELDS;
+    const RUNTIME_FIELDS: bool = T::RUNTIME_FIELDS;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         T::fields()
     }
 }
 
+impl<T: ApplyMask> ApplyMask for Option<T> {
+    fn __apply_mask(&mut self, source: Self, mask: &FieldMask, case: Option<Case>) {
+        match (self, source) {
+            (Some(value), Some(source)) => value.__apply_mask(source, mask, case),
+            (value @ None, source @ Some(_)) if !mask.is_empty() => *value = source,
+            _ => {}
+        }
+    }
+}
+
+impl<T: ApplyMask> ApplyMask for Box<T> {
+    fn __apply_mask(&mut self, source: Self, mask: &FieldMask, case: Option<Case>) {
+        (**self).__apply_mask(*source, mask, case)
+    }
+}
+
+impl_replace_apply_mask!(Result<T, E>, T, E);
+impl_replace_apply_mask!(Vec<T>, T);
+impl_replace_apply_mask!(VecDeque<T>, T);
+impl_replace_apply_mask!(LinkedList<T>, T);
+#[cfg(feature = "std")]
+impl_replace_apply_mask!(HashMap<K, V, S>, K, V, S);
+impl_replace_apply_mask!(BTreeMap<K, V>, K, V);
+#[cfg(feature = "std")]
+impl_replace_apply_mask!(HashSet<T, S>, T, S);
+impl_replace_apply_mask!(BTreeSet<T>, T);
+impl_replace_apply_mask!(BinaryHeap<T>, T);
+
 impl_none_fields!(bool);
 impl_none_fields!(String);
 impl_none_fields!(usize);
@@ -94,9 +178,37 @@ impl_none_fields!(i128);
 impl_none_fields!(f32);
 impl_none_fields!(f64);
 
+impl_replace_apply_mask!(bool);
+impl_replace_apply_mask!(String);
+impl_replace_apply_mask!(usize);
+impl_replace_apply_mask!(u8);
+impl_replace_apply_mask!(u16);
+impl_replace_apply_mask!(u32);
+impl_replace_apply_mask!(u64);
+impl_replace_apply_mask!(u128);
+impl_replace_apply_mask!(i8);
+impl_replace_apply_mask!(i16);
+impl_replace_apply_mask!(i32);
+impl_replace_apply_mask!(i64);
+impl_replace_apply_mask!(i128);
+impl_replace_apply_mask!(f32);
+impl_replace_apply_mask!(f64);
+
 #[cfg(feature = "chrono")]
 impl<Tz: chrono::TimeZone> Fields for chrono::DateTime<Tz> {
-    fn fields() -> &'static Option<HashSet<String>> {
+    const FIELDS: Option<&'static [Field]> = None;
+    const RUNTIME_FIELDS: bool = false;
+
+    fn fields() -> &'static Option<FieldNameSet> {
         &None
     }
 }
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> ApplyMask for chrono::DateTime<Tz> {
+    fn __apply_mask(&mut self, source: Self, mask: &FieldMask, _: Option<Case>) {
+        if !mask.is_empty() {
+            *self = source;
+        }
+    }
+}
@@ -2,12 +2,191 @@
 //!
 //! Fields is a library that gives you a possibility to automatically derive
 //! field names of selected structures and enums with the use of a macro.
-mod instances;
+//!
+//! The `std` feature is enabled by default. With only the `alloc` feature the
+//! crate is `no_std`, and targets without atomic compare-and-swap enable the
+//! `critical-section` feature instead.
+#![no_std]
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("`fields` requires either the `std` or the `alloc` feature");
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
-pub use fields_derive::Fields;
 #[doc(hidden)]
-pub use once_cell::sync::OnceCell;
+pub mod __private;
+mod apply_mask;
+mod dynamic;
+mod field;
+mod field_mask;
+mod field_set;
+mod instances;
+mod meta;
+mod registry;
+mod version;
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+pub use apply_mask::ApplyMask;
+pub use dynamic::DynFields;
+pub use field::Field;
+pub use field_mask::{FieldMask, FieldMaskError};
+pub use field_set::FieldSet;
+pub use fields_case::Case;
+pub use fields_derive::{path, Fields};
+pub use meta::MetaValue;
+#[cfg(target_has_atomic = "ptr")]
+pub use registry::registered_types;
+pub use registry::{Registry, TypeFields};
+
+/// Set of field names returned by [Fields::fields], a [FieldSet].
+pub type FieldNameSet = FieldSet;
 
 pub trait Fields {
-    fn fields() -> &'static Option<std::collections::HashSet<String>>;
+    /// Description of the fields of the type, `None` for types without
+    /// fields. Available in const contexts. Manual implementations that
+    /// don't provide it are treated as types without fields by everything
+    /// built on it, e.g. [path!] and [Fields::field], while derived types
+    /// nesting them take their paths from [Fields::fields] at runtime.
+    const FIELDS: Option<&'static [Field]> = None;
+
+    /// Whether types nesting this one take its paths from [Fields::fields]
+    /// at runtime, because [Fields::FIELDS] doesn't describe them.
+    /// Implementations for types without fields set it to `false`.
+    #[doc(hidden)]
+    const RUNTIME_FIELDS: bool = Self::FIELDS.is_none();
+
+    /// Description of the type, taken from its doc comment.
+    const DOC: Option<&'static str> = None;
+
+    /// User metadata of the type, set with `#[fields(meta(...))]`.
+    const META: &'static [(&'static str, MetaValue)] = &[];
+
+    fn fields() -> &'static Option<FieldNameSet>;
+
+    /// Owned copy of [Fields::fields]. Sets built at compile time are copied
+    /// without allocating.
+    fn field_set() -> Option<FieldSet> {
+        Self::fields().clone()
+    }
+
+    /// Field paths belonging to `group`. A path belongs to the group when
+    /// every field along it does. Fields of types that don't assign any
+    /// groups, and unnamed fields without groups, belong to all groups of
+    /// their parent.
+    fn fields_in_group(group: &str) -> Option<FieldNameSet> {
+        field::collect_paths(Self::FIELDS, &|siblings, field| {
+            field.groups().contains(&group)
+                || field.groups().is_empty()
+                    && (field.name().is_none()
+                        || siblings.iter().all(|sibling| sibling.groups().is_empty()))
+        })
+    }
+
+    /// Field paths named according to the naming `profile`, e.g. `"json"` or
+    /// `"sql"`. Fields the profile doesn't rename keep their default names.
+    fn fields_for(profile: &str) -> Option<FieldNameSet> {
+        field::collect_named_paths(Self::FIELDS, &|_, _| true, &|field, case| match field
+            .profiles()
+            .iter()
+            .find(|(candidate, _)| *candidate == profile)
+        {
+            Some((_, name)) => Some(Cow::Borrowed(name)),
+            None => field::cased_name(field, case).map(Cow::Borrowed),
+        })
+    }
+
+    /// Field paths with the identifiers of the fields converted to `case`.
+    /// Fields renamed with `rename` keep their names, while `rename_all` is
+    /// overridden.
+    fn fields_with_case(case: Case) -> Option<FieldNameSet> {
+        field::collect_named_paths(Self::FIELDS, &|_, _| true, &|field, _| {
+            if field.is_renamed() {
+                field.name().map(Cow::Borrowed)
+            } else {
+                field.ident().map(|ident| Cow::Owned(case.apply(ident)))
+            }
+        })
+    }
+
+    /// Canonical form of `path`, whose segments may be aliases at any
+    /// nesting level, e.g. for accepting old names after a rename. `None` if
+    /// the path doesn't exist.
+    fn canonical_path(path: &str) -> Option<String> {
+        field::canonical_path(Self::FIELDS, path)
+    }
+
+    /// Description of the field at `path`, which may use aliases. `None` for
+    /// paths below a field whose nested fields are provided at runtime by a
+    /// `with` function.
+    fn field(path: &str) -> Option<&'static Field> {
+        field::field_at(Self::FIELDS, path)
+    }
+
+    /// Description of the field at `path`, taken from its doc comment.
+    fn description(path: &str) -> Option<&'static str> {
+        Self::field(path)?.doc()
+    }
+
+    /// Deprecation reason of the field at `path`, `None` if neither the
+    /// field nor any of its parents is deprecated or the path doesn't exist.
+    fn deprecation(path: &str) -> Option<&'static str> {
+        field::fields_along(Self::FIELDS, path)?
+            .iter()
+            .find_map(|field| field.deprecated())
+    }
+
+    /// User metadata of the field at `path`, merged along the path so that
+    /// values set on nested fields override the ones of their parents.
+    fn meta(path: &str) -> Option<BTreeMap<&'static str, MetaValue>> {
+        let along = field::fields_along(Self::FIELDS, path)?;
+        Some(
+            along
+                .iter()
+                .flat_map(|field| field.meta().iter().copied())
+                .collect(),
+        )
+    }
+
+    /// Field paths holding sensitive data: paths with a sensitive field
+    /// anywhere along them.
+    fn sensitive_fields() -> Option<FieldNameSet> {
+        let fields = Self::fields().as_ref()?;
+        Some(
+            fields
+                .iter()
+                .filter(|path| {
+                    field::fields_along(Self::FIELDS, path)
+                        .is_some_and(|along| along.iter().any(|field| field.is_sensitive()))
+                })
+                .collect(),
+        )
+    }
+
+    /// Field paths available in `version`: paths whose every field was
+    /// introduced in `version` or earlier and removed after `version`. Fields
+    /// without `since` or `until` versions are not restricted by them.
+    fn fields_at_version(version: &str) -> Option<FieldNameSet> {
+        field::collect_paths(Self::FIELDS, &|_, field| {
+            field
+                .since()
+                .is_none_or(|since| version::compare(since, version).is_le())
+                && field
+                    .until()
+                    .is_none_or(|until| version::compare(version, until).is_lt())
+        })
+    }
+}
+
+/// Field paths of a type known at compile time, without lazy initialization
+/// or allocation. Derived for types without generic parameters. Using it fails
+/// compilation for types containing fields provided by a `with` function.
+pub trait FieldNames: Fields {
+    /// Distinct field paths in declaration order, the same paths that
+    /// [Fields::fields] returns.
+    const FIELD_NAMES: &'static [&'static str];
 }
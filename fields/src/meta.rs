@@ -0,0 +1,42 @@
+/// Value of user metadata attached with `#[fields(meta(key = value))]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetaValue {
+    Str(&'static str),
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+}
+
+impl MetaValue {
+    /// The string, if the value is a [MetaValue::Str].
+    pub const fn as_str(&self) -> Option<&'static str> {
+        match self {
+            MetaValue::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The bool, if the value is a [MetaValue::Bool].
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            MetaValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The integer, if the value is a [MetaValue::Int].
+    pub const fn as_int(&self) -> Option<i64> {
+        match self {
+            MetaValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The float, if the value is a [MetaValue::Float].
+    pub const fn as_float(&self) -> Option<f64> {
+        match self {
+            MetaValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
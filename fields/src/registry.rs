@@ -0,0 +1,203 @@
+use core::any::{type_name, Any, TypeId};
+
+use alloc::collections::BTreeMap;
+
+use crate::{DynFields, Field, FieldNameSet, Fields};
+
+/// Fields of a type, stored in a [Registry] under a name.
+#[derive(Debug, Clone, Copy)]
+pub struct TypeFields {
+    name: &'static str,
+    type_name: &'static str,
+    type_id: TypeId,
+    module_path: Option<&'static str>,
+    fields: fn() -> &'static Option<FieldNameSet>,
+    field_tree: Option<&'static [Field]>,
+}
+
+impl TypeFields {
+    /// Fields of `T`, named after the last segment of its path without
+    /// generic arguments, e.g. `User` for `my_crate::model::User`.
+    pub fn of<T: Fields + 'static>() -> Self {
+        Self::named::<T>(short_type_name(type_name::<T>()))
+    }
+
+    /// Fields of `T` under a custom `name`.
+    pub fn named<T: Fields + 'static>(name: &'static str) -> Self {
+        Self {
+            name,
+            type_name: type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+            module_path: None,
+            fields: T::fields,
+            field_tree: T::FIELDS,
+        }
+    }
+
+    /// Name the type is registered under.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Full name of the type.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Module the type is defined in, known for types registered with
+    /// `#[fields(register)]`.
+    pub fn module_path(&self) -> Option<&'static str> {
+        self.module_path
+    }
+
+    /// Record the module the type is defined in, e.g. `module_path!()`.
+    pub fn with_module_path(mut self, module_path: &'static str) -> Self {
+        self.module_path = Some(module_path);
+        self
+    }
+
+    /// Field paths of the type, see [Fields::fields].
+    pub fn fields(&self) -> &'static Option<FieldNameSet> {
+        (self.fields)()
+    }
+
+    /// Description of the fields of the type, see [Fields::FIELDS].
+    pub fn field_tree(&self) -> Option<&'static [Field]> {
+        self.field_tree
+    }
+}
+
+impl DynFields for TypeFields {
+    fn dyn_fields(&self) -> &'static Option<FieldNameSet> {
+        self.fields()
+    }
+
+    fn dyn_field_tree(&self) -> Option<&'static [Field]> {
+        self.field_tree
+    }
+
+    fn dyn_type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+/// Last segment of a type path without generic arguments.
+fn short_type_name(type_name: &'static str) -> &'static str {
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Types deriving [Fields] with `#[fields(register)]`, in no particular order.
+///
+/// Types are registered by constructors run when the program starts, on
+/// platforms whose executables support them: Linux and other ELF targets,
+/// macOS, iOS and Windows. Types of a library crate are only registered if the
+/// linker keeps the code defining them, i.e. if the library is used by the
+/// program. Registration needs pointer-sized atomics, so this isn't available
+/// on targets without them.
+#[cfg(target_has_atomic = "ptr")]
+pub fn registered_types() -> impl Iterator<Item = TypeFields> {
+    crate::__private::registrations().map(|registration| registration.type_fields())
+}
+
+/// Fields of types looked up by name or by [TypeId] at runtime, e.g. for
+/// entities of a plugin system.
+///
+/// ```rust
+/// use std::any::Any;
+/// use fields::{Fields, Registry};
+///
+/// #[derive(Fields)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// let mut registry = Registry::new();
+/// registry.register::<User>();
+///
+/// let user = registry.get("User").unwrap();
+/// assert!(user.fields().as_ref().unwrap().contains("name"));
+///
+/// let value: &dyn Any = &User { name: "john".to_string() };
+/// assert_eq!(registry.get_for(value).unwrap().name(), "User");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Registry {
+    by_name: BTreeMap<&'static str, TypeFields>,
+    by_type_id: BTreeMap<TypeId, &'static str>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry of all [registered_types]. Of several types registered under
+    /// the same name, only one is kept.
+    #[cfg(target_has_atomic = "ptr")]
+    pub fn registered() -> Self {
+        let mut registry = Self::new();
+        for fields in registered_types() {
+            registry.insert(fields);
+        }
+        registry
+    }
+
+    /// Register `T` under the name given by [TypeFields::of].
+    pub fn register<T: Fields + 'static>(&mut self) -> &mut Self {
+        self.insert(TypeFields::of::<T>());
+        self
+    }
+
+    /// Register `T` under a custom `name`.
+    pub fn register_as<T: Fields + 'static>(&mut self, name: &'static str) -> &mut Self {
+        self.insert(TypeFields::named::<T>(name));
+        self
+    }
+
+    /// Register the fields of a type, returning the fields previously
+    /// registered under the same name.
+    pub fn insert(&mut self, fields: TypeFields) -> Option<TypeFields> {
+        let previous = self.by_name.insert(fields.name(), fields);
+        if let Some(previous) = previous {
+            // The type may have been registered again under another name since.
+            if self.by_type_id.get(&previous.type_id()) == Some(&previous.name()) {
+                self.by_type_id.remove(&previous.type_id());
+            }
+        }
+        self.by_type_id.insert(fields.type_id(), fields.name());
+        previous
+    }
+
+    /// Fields of the type registered under `name`.
+    pub fn get(&self, name: &str) -> Option<&TypeFields> {
+        self.by_name.get(name)
+    }
+
+    /// Fields of the type with the given [TypeId].
+    pub fn get_by_type_id(&self, type_id: TypeId) -> Option<&TypeFields> {
+        self.by_name.get(self.by_type_id.get(&type_id)?)
+    }
+
+    /// Fields of the type of `value`.
+    pub fn get_for(&self, value: &dyn Any) -> Option<&TypeFields> {
+        self.get_by_type_id(value.type_id())
+    }
+
+    /// Registered types ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = &TypeFields> {
+        self.by_name.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+}
@@ -0,0 +1,25 @@
+use core::cmp::Ordering;
+
+/// Compare dotted versions component by component, numerically where both
+/// components are numbers, e.g. `1.4 < 1.10`. Missing components count as
+/// `0`, so `1.4 == 1.4.0`.
+pub(crate) fn compare(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (a, b) => compare_component(a.unwrap_or("0"), b.unwrap_or("0")),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+fn compare_component(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}